@@ -0,0 +1,80 @@
+//! Concurrency admission control for provider dispatch.
+//!
+//! Without a cap, the gateway will happily launch unbounded concurrent provider calls,
+//! turning load spikes into cascading timeouts. [`AdmissionControl`] wraps an
+//! `Arc<Semaphore>` (as in text-embeddings-inference's `Infer`) so callers acquire an owned
+//! permit before dispatching to a provider and hold it for the lifetime of the inference —
+//! including, for streaming, the lifetime of the response stream itself, since the permit is
+//! only released when the stream is dropped or completes.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::error::{Error, ErrorDetails};
+
+/// Bounds how many provider calls may be in flight at once for a given model.
+#[derive(Debug, Clone)]
+pub struct AdmissionControl {
+    semaphore: Arc<Semaphore>,
+}
+
+impl AdmissionControl {
+    pub fn new(max_concurrent_requests: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent_requests)),
+        }
+    }
+
+    /// Acquires a permit immediately, returning [`ErrorDetails::Overloaded`] (mapped to an
+    /// HTTP 429 by the gateway) instead of queueing indefinitely when none is available.
+    pub fn try_acquire(&self) -> Result<OwnedSemaphorePermit, Error> {
+        self.semaphore
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| ErrorDetails::Overloaded.into())
+    }
+
+    /// Like [`Self::try_acquire`], but waits up to `max_wait` for a permit to free up before
+    /// giving up with [`ErrorDetails::Overloaded`]. Useful for callers that would rather
+    /// absorb a brief burst than fail it outright.
+    pub async fn acquire_with_timeout(
+        &self,
+        max_wait: Duration,
+    ) -> Result<OwnedSemaphorePermit, Error> {
+        tokio::time::timeout(max_wait, self.semaphore.clone().acquire_owned())
+            .await
+            .map_err(|_| Error::new(ErrorDetails::Overloaded))?
+            .map_err(|_| Error::new(ErrorDetails::Overloaded))
+    }
+}
+
+/// Wraps a `PeekableProviderInferenceResponseStream`-backing stream so an
+/// [`OwnedSemaphorePermit`] is held for as long as the stream is alive, releasing
+/// concurrency capacity only once the stream is dropped (normal completion, early drop on
+/// client disconnect, or error) rather than as soon as the first chunk is produced.
+pub struct PermitHeldStream<S> {
+    inner: S,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<S> PermitHeldStream<S> {
+    pub fn new(inner: S, permit: OwnedSemaphorePermit) -> Self {
+        Self {
+            inner,
+            _permit: permit,
+        }
+    }
+}
+
+impl<S: futures::Stream + Unpin> futures::Stream for PermitHeldStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        std::pin::Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
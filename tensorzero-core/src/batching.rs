@@ -0,0 +1,155 @@
+//! Continuous batching for non-streaming inference.
+//!
+//! Individual `/inference` calls to the same model are coalesced into a single provider
+//! call, following the `batching_task` pattern used by text-generation-inference: a
+//! background task owns a [`Queue`] of pending [`Entry`] values, is woken by a
+//! [`tokio::sync::Notify`] whenever a new entry is enqueued, and drains up to
+//! `max_batch_size` entries (or flushes early once `max_waiting_ms` has elapsed) before
+//! submitting the coalesced batch to the provider and fanning the results back out by index.
+//! Streaming requests bypass the batcher entirely and call the provider directly.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{oneshot, Mutex, Notify};
+use tokio::time::Instant;
+
+use crate::error::{Error, ErrorDetails};
+use crate::inference::types::{ModelInferenceRequest, ProviderInferenceResponse};
+
+/// Per-model batching configuration. `max_batch_size` bounds how many requests are folded
+/// into a single provider call; `max_waiting_ms` bounds how long the oldest entry in the
+/// queue waits before the batch is flushed even if it isn't full.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[cfg_attr(test, ts(export))]
+pub struct BatchingConfig {
+    pub max_batch_size: usize,
+    pub max_waiting_ms: u64,
+}
+
+impl Default for BatchingConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 4,
+            max_waiting_ms: 20,
+        }
+    }
+}
+
+/// One inference request waiting to be folded into the next provider batch, and the
+/// one-shot channel used to hand its individual result back to the caller that enqueued it.
+pub struct Entry {
+    pub request: ModelInferenceRequest<'static>,
+    pub responder: oneshot::Sender<Result<ProviderInferenceResponse, Error>>,
+}
+
+/// FIFO queue of pending entries, behind a `Mutex` so callers can enqueue concurrently while
+/// the background batching task drains it.
+#[derive(Default)]
+struct Queue {
+    entries: Mutex<Vec<Entry>>,
+}
+
+/// A trait implemented by providers that can service a coalesced batch of
+/// `ModelInferenceRequest`s in a single call, returning one `ProviderInferenceResponse` per
+/// request in the same order. `DummyProvider` implements this on top of its existing
+/// `start_batch_inference`/`poll_batch_inference` machinery so the batcher is testable
+/// end-to-end without a real HTTP provider.
+#[trait_variant::make(Send)]
+pub trait BatchSubmit {
+    async fn submit_batch(
+        &self,
+        requests: Vec<ModelInferenceRequest<'static>>,
+    ) -> Result<Vec<Result<ProviderInferenceResponse, Error>>, Error>;
+}
+
+/// Spawns the background batching task for a single model and returns a handle that
+/// `enqueue`s individual requests onto it. Dropping the handle's `Arc` stops new work from
+/// being accepted, but in-flight batches still complete.
+pub struct Batcher {
+    queue: Arc<Queue>,
+    notify: Arc<Notify>,
+    config: BatchingConfig,
+}
+
+impl Batcher {
+    pub fn spawn<P>(provider: Arc<P>, config: BatchingConfig) -> Arc<Self>
+    where
+        P: BatchSubmit + Send + Sync + 'static,
+    {
+        let queue = Arc::new(Queue::default());
+        let notify = Arc::new(Notify::new());
+        let batcher = Arc::new(Self {
+            queue: queue.clone(),
+            notify: notify.clone(),
+            config,
+        });
+        tokio::spawn(batching_task(queue, notify, provider, config));
+        batcher
+    }
+
+    /// Enqueues a single inference request and returns its eventual result once the
+    /// background task has folded it into a batch and dispatched it to the provider.
+    pub async fn enqueue(
+        &self,
+        request: ModelInferenceRequest<'static>,
+    ) -> Result<ProviderInferenceResponse, Error> {
+        let (responder, receiver) = oneshot::channel();
+        self.queue
+            .entries
+            .lock()
+            .await
+            .push(Entry { request, responder });
+        self.notify.notify_one();
+        receiver.await.map_err(|_| {
+            Error::new(ErrorDetails::InternalError {
+                message: "Batcher dropped an entry's responder before replying".to_string(),
+            })
+        })?
+    }
+}
+
+async fn batching_task<P>(
+    queue: Arc<Queue>,
+    notify: Arc<Notify>,
+    provider: Arc<P>,
+    config: BatchingConfig,
+) where
+    P: BatchSubmit + Send + Sync + 'static,
+{
+    loop {
+        notify.notified().await;
+        let deadline = Instant::now() + Duration::from_millis(config.max_waiting_ms);
+        let batch = loop {
+            let mut entries = queue.entries.lock().await;
+            if entries.len() >= config.max_batch_size || Instant::now() >= deadline {
+                let drained: Vec<Entry> = entries
+                    .drain(..entries.len().min(config.max_batch_size))
+                    .collect();
+                break drained;
+            }
+            drop(entries);
+            tokio::time::sleep_until(deadline.min(Instant::now() + Duration::from_millis(1))).await;
+        };
+        if batch.is_empty() {
+            continue;
+        }
+        let (requests, responders): (Vec<_>, Vec<_>) = batch
+            .into_iter()
+            .map(|entry| (entry.request, entry.responder))
+            .unzip();
+        match provider.submit_batch(requests).await {
+            Ok(results) => {
+                for (responder, result) in responders.into_iter().zip(results) {
+                    let _ = responder.send(result);
+                }
+            }
+            Err(e) => {
+                for responder in responders {
+                    let _ = responder.send(Err(e.clone()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! The model-inference cache policy threaded alongside each request via
+//! `endpoints::inference::InferenceClients`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::inference::types::ModelInferenceRequest;
+
+/// Identifies a single provider call within a model's routing list, for cache-key purposes.
+#[derive(Debug, Clone, Copy)]
+pub struct ModelProviderRequest<'a> {
+    pub request: &'a ModelInferenceRequest<'a>,
+    pub provider_name: &'a str,
+    pub model_name: &'a str,
+}
+
+/// Governs whether the model-inference cache is consulted, written to, both, or neither for a
+/// given request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheEnabledMode {
+    On,
+    Off,
+    ReadOnly,
+    WriteOnly,
+}
+
+/// Per-request cache policy: whether the inference cache is active, and (if so) how long a
+/// cached entry stays valid.
+#[derive(Debug, Clone, Copy)]
+pub struct CacheOptions {
+    pub max_age_s: Option<u32>,
+    pub enabled: CacheEnabledMode,
+}
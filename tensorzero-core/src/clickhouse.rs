@@ -0,0 +1,11 @@
+//! Connection handle for the ClickHouse instance inference/feedback writes land in.
+
+/// How (or whether) this process is connected to ClickHouse.
+///
+/// `Disabled` is what every inference path in this tree constructs today — the variants an
+/// actually-configured gateway would use (a real HTTP connection, or a mock for integration
+/// tests) live in the observability layer, which isn't part of this snapshot.
+#[derive(Debug, Clone)]
+pub enum ClickHouseConnectionInfo {
+    Disabled,
+}
@@ -0,0 +1,85 @@
+//! Embedding providers: a single-input `embed` call, and the batched `embed_batch` variant that
+//! amortizes one round-trip across many inputs instead of requiring one call per input.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::endpoints::inference::InferenceCredentials;
+use crate::error::Error;
+use crate::inference::types::{Latency, Usage};
+
+/// A request to embed one or more inputs. `input` is kept alongside `inputs` (rather than
+/// folded into a single-element `inputs`) so `embed`'s existing single-input callers don't need
+/// to allocate a `Vec` just to embed one string.
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingRequest {
+    pub input: String,
+    pub inputs: Vec<String>,
+}
+
+/// Result of embedding a single input.
+#[derive(Debug, Clone)]
+pub struct EmbeddingProviderResponse {
+    pub id: Uuid,
+    pub input: String,
+    pub embedding: Vec<f32>,
+    pub created: u64,
+    pub raw_request: String,
+    pub raw_response: String,
+    pub usage: Usage,
+    pub latency: Latency,
+}
+
+/// Result of embedding many inputs in a single provider call. Mirrors
+/// [`EmbeddingProviderResponse`], but carries one embedding per input instead of a single
+/// vector, and preserves input order in `embeddings`.
+#[derive(Debug, Clone)]
+pub struct EmbeddingBatchProviderResponse {
+    pub id: Uuid,
+    pub inputs: Vec<String>,
+    pub embeddings: Vec<Vec<f32>>,
+    pub created: u64,
+    pub raw_request: String,
+    pub raw_response: String,
+    pub usage: Usage,
+    pub latency: Latency,
+}
+
+/// A provider capable of producing embeddings for text input.
+pub trait EmbeddingProvider {
+    /// Embeds `request.input`.
+    async fn embed(
+        &self,
+        request: &EmbeddingRequest,
+        http_client: &reqwest::Client,
+        dynamic_api_keys: &InferenceCredentials,
+    ) -> Result<EmbeddingProviderResponse, Error>;
+
+    /// Embeds every input in `request.inputs` in a single call instead of requiring one
+    /// round-trip per input, the way text-embeddings-inference processes batches.
+    async fn embed_batch(
+        &self,
+        request: &EmbeddingRequest,
+        http_client: &reqwest::Client,
+        dynamic_api_keys: &InferenceCredentials,
+    ) -> Result<EmbeddingBatchProviderResponse, Error>;
+}
+
+/// Config for a single embedding model. Empty for now — this tree has no embedding model
+/// provider besides `DummyProvider`, so there's no routing/provider-table shape to confirm yet.
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingModelConfig {}
+
+/// The table of configured embedding models, keyed by name. Mirrors `model::ModelTable`'s
+/// construction shape so config loading can treat the two tables the same way.
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingModelTable(HashMap<String, EmbeddingModelConfig>);
+
+impl TryFrom<HashMap<String, EmbeddingModelConfig>> for EmbeddingModelTable {
+    type Error = Error;
+
+    fn try_from(map: HashMap<String, EmbeddingModelConfig>) -> Result<Self, Error> {
+        Ok(Self(map))
+    }
+}
@@ -0,0 +1,79 @@
+//! Request-scoped state and parameters threaded through a single inference call: the
+//! credentials available for dynamic API keys, the cache policy, the shared ClickHouse handle,
+//! and the sampling parameters a variant may override per-provider.
+
+use std::collections::HashMap;
+
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::cache::CacheOptions;
+use crate::clickhouse::ClickHouseConnectionInfo;
+use crate::embeddings::EmbeddingModelTable;
+use crate::model::ModelTable;
+use crate::tool_cache::ToolResultCache;
+use crate::variant::JsonMode;
+
+/// Dynamic, per-request API keys, keyed by the name a provider's `api_key_location` config
+/// refers to (e.g. `CredentialLocation::Dynamic("my_key".to_string())`).
+#[derive(Debug, Clone, Default)]
+pub struct InferenceCredentials(HashMap<String, SecretString>);
+
+impl InferenceCredentials {
+    pub fn get(&self, key_name: &str) -> Option<&SecretString> {
+        self.0.get(key_name)
+    }
+}
+
+/// The identifiers assigned to a single inference request within its episode.
+#[derive(Debug, Clone, Copy)]
+pub struct InferenceIds {
+    pub inference_id: Uuid,
+    pub episode_id: Uuid,
+}
+
+/// The model and embedding-model tables available to resolve a variant's configured model
+/// names against, bundled together since a single inference may need both.
+#[derive(Debug, Clone, Copy)]
+pub struct InferenceModels<'a> {
+    pub models: &'a ModelTable,
+    pub embedding_models: &'a EmbeddingModelTable,
+}
+
+/// The clients and shared, request-scoped state a variant needs to actually dispatch an
+/// inference: the HTTP client used for both model providers and tool executors, the ClickHouse
+/// handle, dynamic credentials, the model-inference cache policy, and the tool-result cache.
+pub struct InferenceClients<'a> {
+    pub http_client: &'a reqwest::Client,
+    pub clickhouse_connection_info: &'a ClickHouseConnectionInfo,
+    pub credentials: &'a InferenceCredentials,
+    pub cache_options: &'a CacheOptions,
+    /// Shared across every tool call made during this request, so a deterministic tool invoked
+    /// more than once in a multi-step tool-calling loop can skip re-executing it. See
+    /// [`crate::tool_cache`].
+    pub tool_result_cache: &'a ToolResultCache,
+}
+
+/// Sampling parameters a `ChatCompletionConfig` exposes for per-request override, mirroring the
+/// variant's own configured defaults field-for-field so either can be laid over the other.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatCompletionInferenceParams {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub presence_penalty: Option<f32>,
+    pub frequency_penalty: Option<f32>,
+    pub seed: Option<u32>,
+    pub stop_sequences: Option<Vec<String>>,
+    pub json_mode: Option<JsonMode>,
+}
+
+/// Per-variant-kind sampling parameter overrides for a single inference request. Currently only
+/// chat-completion variants expose any, so this only has the one field, but it's a struct
+/// (rather than `ChatCompletionInferenceParams` itself) so other variant kinds can grow their
+/// own override sets alongside it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InferenceParams {
+    pub chat_completion: ChatCompletionInferenceParams,
+}
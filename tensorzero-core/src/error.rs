@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use reqwest::StatusCode;
+
+/// The crate-wide error type. Every fallible operation that crosses a module boundary returns
+/// `Result<_, Error>`; callers that need to branch on the failure mode match on
+/// [`Error::get_details`] rather than the `Display` string.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    details: ErrorDetails,
+}
+
+impl Error {
+    pub fn new(details: ErrorDetails) -> Self {
+        Self { details }
+    }
+
+    /// Returns the structured detail behind this error, for callers that need to branch on the
+    /// failure mode rather than match against the `Display` string.
+    pub fn get_details(&self) -> &ErrorDetails {
+        &self.details
+    }
+
+    /// The HTTP status the gateway should respond with when this error reaches its boundary.
+    pub fn status_code(&self) -> StatusCode {
+        self.details.status_code()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.details, f)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ErrorDetails> for Error {
+    fn from(details: ErrorDetails) -> Self {
+        Error::new(details)
+    }
+}
+
+/// The structured detail behind an [`Error`]. Each variant carries exactly the context its
+/// producers and consumers across the crate already agree on.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorDetails {
+    /// No API key is configured for a provider that requires one.
+    ApiKeyMissing { provider_name: String },
+    /// A configuration value is missing, malformed, or otherwise invalid.
+    Config { message: String },
+    /// A provider's HTTP call failed, or the provider returned a response this crate can't use.
+    InferenceClient {
+        message: String,
+        raw_request: Option<String>,
+        raw_response: Option<String>,
+        status_code: Option<StatusCode>,
+        provider_type: String,
+    },
+    /// An invariant internal to this crate was violated; this should never surface from
+    /// well-formed input and indicates a bug here rather than in a caller or provider.
+    InternalError { message: String },
+    /// A request message's content doesn't have a shape this variant knows how to render.
+    InvalidMessage { message: String },
+    /// A variant was configured with a template but no path was resolved for it.
+    InvalidTemplatePath,
+    /// Rendering a MiniJinja template failed.
+    MiniJinjaTemplateRender {
+        message: String,
+        template_name: String,
+    },
+    /// Every model provider for a model failed; the per-provider failures are attached.
+    ModelProvidersExhausted {
+        provider_errors: HashMap<String, Error>,
+    },
+    /// The server is at its configured concurrency limit and the caller should back off.
+    Overloaded,
+    /// Serializing a value to or from JSON failed.
+    Serialization { message: String },
+    /// A registered tool handler returned an error instead of a result.
+    ToolHandlerFailed { tool_name: String, message: String },
+    /// A tool-calling loop hit its configured step limit with a tool call still pending.
+    ToolStepsExhausted { max_tool_steps: usize },
+    /// A variant referenced a model that isn't defined in the model table.
+    UnknownModel { name: String },
+    /// A provider was asked to do something its capabilities don't cover.
+    UnsupportedCapability {
+        provider_name: String,
+        capability: String,
+    },
+    /// A model provider type doesn't support batch inference.
+    UnsupportedModelProviderForBatchInference { provider_type: String },
+}
+
+impl ErrorDetails {
+    /// The HTTP status the gateway should respond with when this error reaches its boundary.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ErrorDetails::ApiKeyMissing { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorDetails::Config { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorDetails::InferenceClient { status_code, .. } => {
+                status_code.unwrap_or(StatusCode::BAD_GATEWAY)
+            }
+            ErrorDetails::InternalError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorDetails::InvalidMessage { .. } => StatusCode::BAD_REQUEST,
+            ErrorDetails::InvalidTemplatePath => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorDetails::MiniJinjaTemplateRender { .. } => StatusCode::BAD_REQUEST,
+            ErrorDetails::ModelProvidersExhausted { .. } => StatusCode::BAD_GATEWAY,
+            ErrorDetails::Overloaded => StatusCode::TOO_MANY_REQUESTS,
+            ErrorDetails::Serialization { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorDetails::ToolHandlerFailed { .. } => StatusCode::BAD_GATEWAY,
+            ErrorDetails::ToolStepsExhausted { .. } => StatusCode::BAD_GATEWAY,
+            ErrorDetails::UnknownModel { .. } => StatusCode::BAD_REQUEST,
+            ErrorDetails::UnsupportedCapability { .. } => StatusCode::BAD_REQUEST,
+            ErrorDetails::UnsupportedModelProviderForBatchInference { .. } => {
+                StatusCode::BAD_REQUEST
+            }
+        }
+    }
+}
+
+impl fmt::Display for ErrorDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorDetails::ApiKeyMissing { provider_name } => {
+                write!(f, "No API key is set for provider '{provider_name}'")
+            }
+            ErrorDetails::Config { message } => write!(f, "Configuration error: {message}"),
+            ErrorDetails::InferenceClient {
+                message,
+                provider_type,
+                ..
+            } => write!(f, "Error from provider '{provider_type}': {message}"),
+            ErrorDetails::InternalError { message } => write!(f, "Internal error: {message}"),
+            ErrorDetails::InvalidMessage { message } => write!(f, "Invalid message: {message}"),
+            ErrorDetails::InvalidTemplatePath => {
+                write!(
+                    f,
+                    "A variant template is configured but its path did not resolve"
+                )
+            }
+            ErrorDetails::MiniJinjaTemplateRender {
+                message,
+                template_name,
+            } => write!(f, "Error rendering template '{template_name}': {message}"),
+            ErrorDetails::ModelProvidersExhausted { provider_errors } => {
+                write!(f, "All model providers failed: ")?;
+                for (i, (provider_name, error)) in provider_errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{provider_name}: {error}")?;
+                }
+                Ok(())
+            }
+            ErrorDetails::Overloaded => {
+                write!(
+                    f,
+                    "Server is overloaded, no capacity available for this request"
+                )
+            }
+            ErrorDetails::Serialization { message } => {
+                write!(f, "Error serializing or deserializing data: {message}")
+            }
+            ErrorDetails::ToolHandlerFailed { tool_name, message } => {
+                write!(f, "Tool '{tool_name}' failed: {message}")
+            }
+            ErrorDetails::ToolStepsExhausted { max_tool_steps } => write!(
+                f,
+                "Tool-calling loop did not resolve within {max_tool_steps} step(s)"
+            ),
+            ErrorDetails::UnknownModel { name } => write!(f, "Unknown model: '{name}'"),
+            ErrorDetails::UnsupportedCapability {
+                provider_name,
+                capability,
+            } => write!(
+                f,
+                "Provider '{provider_name}' does not support capability: {capability}"
+            ),
+            ErrorDetails::UnsupportedModelProviderForBatchInference { provider_type } => write!(
+                f,
+                "Model provider type '{provider_type}' does not support batch inference"
+            ),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Configurable, deterministic fault injection usable against *any* provider.
+//!
+//! `DummyProvider` has grown a handful of ad-hoc sentinel `model_name`s over time
+//! (`err_in_stream`, `slow_second_chunk`, `tool_split_name`, the `flaky_*` prefix, and the
+//! `fault::key=val` spec parsed in [`crate::providers::dummy`]) to drive chaos/timeout tests.
+//! Those stay as-is here — existing tests pin down their exact string-keyed behavior, and
+//! swapping them out is a separate, larger change than this module makes.
+//!
+//! [`FaultInjectionConfig`] plus the generic [`FaultInjectingProvider<P>`] wrapper are this
+//! module's real contribution: a declarative fault profile and a wrapper that can sit in front
+//! of *any* `crate::inference::InferenceProvider`, not just the dummy one, so a future provider
+//! (or a future pass over `DummyProvider`'s ad-hoc branches) doesn't have to reinvent
+//! error-rate/latency/stream-abort injection from scratch. Nothing in this tree constructs a
+//! `FaultInjectingProvider` yet — it's standalone infrastructure, not a completed migration.
+
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::error::{Error, ErrorDetails};
+use crate::inference::types::ProviderInferenceResponseChunk;
+
+/// Added latency before a response (or, for streaming, before each chunk).
+#[derive(Debug, Clone, Copy)]
+pub enum LatencyProfile {
+    None,
+    Fixed(Duration),
+    Random { min: Duration, max: Duration },
+}
+
+impl LatencyProfile {
+    fn sample(self) -> Duration {
+        match self {
+            LatencyProfile::None => Duration::ZERO,
+            LatencyProfile::Fixed(d) => d,
+            LatencyProfile::Random { min, max } => {
+                if max <= min {
+                    min
+                } else {
+                    let extra = rand::thread_rng().gen_range(0..=(max - min).as_millis());
+                    min + Duration::from_millis(extra as u64)
+                }
+            }
+        }
+    }
+}
+
+impl Default for LatencyProfile {
+    fn default() -> Self {
+        LatencyProfile::None
+    }
+}
+
+/// A declarative fault-injection profile, independent of any particular provider
+/// implementation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FaultInjectionConfig {
+    /// Probability (0.0-1.0) that any given non-streaming call fails outright.
+    pub error_rate: f64,
+    /// Latency added before the (non-streaming) response, or before each streamed chunk.
+    pub latency: LatencyProfile,
+    /// For streaming providers: the 0-indexed chunk at which to abort the stream with an
+    /// error after that many good chunks have already been yielded.
+    pub stream_error_at: Option<usize>,
+    /// The HTTP status code to attach to any injected error.
+    pub injected_status: Option<u16>,
+}
+
+impl FaultInjectionConfig {
+    fn injected_error(&self, provider_type: &str, message: String) -> Error {
+        ErrorDetails::InferenceClient {
+            message,
+            raw_request: Some("raw request".to_string()),
+            raw_response: None,
+            status_code: self
+                .injected_status
+                .and_then(|s| reqwest::StatusCode::from_u16(s).ok()),
+            provider_type: provider_type.to_string(),
+        }
+        .into()
+    }
+
+    /// Sleeps for the configured latency and, if the dice roll says so, returns an error
+    /// instead of `Ok(())`. Callers should `?` this before dispatching to the wrapped
+    /// provider.
+    pub async fn maybe_fail_before_dispatch(&self, provider_type: &str) -> Result<(), Error> {
+        let delay = self.latency.sample();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        if self.error_rate > 0.0 && rand::thread_rng().gen_bool(self.error_rate.clamp(0.0, 1.0)) {
+            return Err(self.injected_error(
+                provider_type,
+                "Injected fault: random error_rate roll failed".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// For a stream that has already yielded `chunks_so_far` good chunks, returns an error
+    /// to abort the stream if this is the configured injection point.
+    pub fn maybe_fail_stream_chunk(
+        &self,
+        provider_type: &str,
+        chunks_so_far: usize,
+    ) -> Option<Error> {
+        if self.stream_error_at == Some(chunks_so_far) {
+            Some(self.injected_error(
+                provider_type,
+                format!("Injected fault after {chunks_so_far} good chunk(s)"),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Wraps any provider `P` so its calls are subject to a [`FaultInjectionConfig`] before
+/// being forwarded, rather than requiring the provider itself to special-case sentinel
+/// model names.
+pub struct FaultInjectingProvider<P> {
+    pub inner: P,
+    pub config: FaultInjectionConfig,
+    pub provider_type: &'static str,
+}
+
+impl<P> FaultInjectingProvider<P> {
+    pub fn new(inner: P, config: FaultInjectionConfig, provider_type: &'static str) -> Self {
+        Self {
+            inner,
+            config,
+            provider_type,
+        }
+    }
+
+    /// Applies the fault-injection pre-check, then runs `dispatch` (the wrapped provider's
+    /// actual call) if the roll succeeded.
+    pub async fn guarded<T, F, Fut>(&self, dispatch: F) -> Result<T, Error>
+    where
+        F: FnOnce(&P) -> Fut,
+        Fut: std::future::Future<Output = Result<T, Error>>,
+    {
+        self.config
+            .maybe_fail_before_dispatch(self.provider_type)
+            .await?;
+        dispatch(&self.inner).await
+    }
+
+    /// Checks whether `chunk_index` is where the configured stream fault should fire,
+    /// returning the injected error if so instead of the real chunk.
+    pub fn guard_chunk(
+        &self,
+        chunk_index: usize,
+        chunk: Result<ProviderInferenceResponseChunk, Error>,
+    ) -> Result<ProviderInferenceResponseChunk, Error> {
+        if let Some(err) = self
+            .config
+            .maybe_fail_stream_chunk(self.provider_type, chunk_index)
+        {
+            return Err(err);
+        }
+        chunk
+    }
+}
@@ -0,0 +1,109 @@
+//! Background provider health monitoring.
+//!
+//! [`spawn_health_watcher`] spawns a background task (following the pattern used by
+//! text-embeddings-inference) that periodically probes a provider and publishes the result on
+//! a [`tokio::sync::watch`] channel, so a caller can hold a cheap [`watch::Receiver<Health>`]
+//! per provider and consult the latest value instead of blocking on a fresh probe for every
+//! inference request. Nothing in this tree spawns a watcher or consults one yet: having the
+//! fallback-routing loop exclude `Unhealthy` providers from candidate selection would mean
+//! holding a `watch::Receiver` alongside each provider in `ModelConfig`'s provider list and
+//! consulting it before dispatch, which belongs in `model.rs` — not present in this tree. This
+//! module is the probing/aggregation machinery that routing integration would build on, not a
+//! completed integration.
+
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+/// The latest known health of a model provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Health {
+    #[default]
+    Healthy,
+    Unhealthy,
+}
+
+/// How the health watcher decides when a provider flips state.
+#[derive(Debug, Clone, Copy)]
+pub struct HealthCheckConfig {
+    /// How often to probe the provider.
+    pub interval: Duration,
+    /// Consecutive probe failures required before a `Healthy` provider is marked `Unhealthy`.
+    pub failure_threshold: u32,
+    /// Consecutive probe successes required before an `Unhealthy` provider recovers.
+    pub recovery_threshold: u32,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            failure_threshold: 3,
+            recovery_threshold: 1,
+        }
+    }
+}
+
+/// A lightweight, provider-specific liveness probe. For HTTP providers this is a cheap
+/// request (e.g. a models-list or completions-with-minimal-tokens call); `DummyProvider`
+/// implements it by keying off `model_name`, mirroring how `err_in_stream`/`error` sentinel
+/// models are handled in its `infer`/`infer_stream` paths.
+#[trait_variant::make(Send)]
+pub trait HealthProbe {
+    async fn probe(&self) -> bool;
+}
+
+/// Spawns the background health-watching task for a single provider and returns the
+/// `watch::Receiver` that the routing layer should consult before selecting it as a
+/// candidate.
+pub fn spawn_health_watcher<P>(probe: P, config: HealthCheckConfig) -> watch::Receiver<Health>
+where
+    P: HealthProbe + Send + Sync + 'static,
+{
+    let (sender, receiver) = watch::channel(Health::Healthy);
+    tokio::spawn(async move {
+        let mut consecutive_failures = 0u32;
+        let mut consecutive_successes = 0u32;
+        loop {
+            tokio::time::sleep(config.interval).await;
+            let healthy = probe.probe().await;
+            if healthy {
+                consecutive_successes += 1;
+                consecutive_failures = 0;
+                if *sender.borrow() == Health::Unhealthy
+                    && consecutive_successes >= config.recovery_threshold
+                {
+                    let _ = sender.send(Health::Healthy);
+                }
+            } else {
+                consecutive_failures += 1;
+                consecutive_successes = 0;
+                if *sender.borrow() == Health::Healthy
+                    && consecutive_failures >= config.failure_threshold
+                {
+                    let _ = sender.send(Health::Unhealthy);
+                }
+            }
+            if sender.is_closed() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Aggregates a set of provider health receivers into a single overall status, e.g. for
+/// surfacing over a `/health` endpoint: the gateway is considered healthy as long as at
+/// least one provider per model is healthy.
+pub fn overall_health<'a>(
+    receivers: impl IntoIterator<Item = &'a watch::Receiver<Health>>,
+) -> Health {
+    if receivers
+        .into_iter()
+        .any(|receiver| *receiver.borrow() == Health::Healthy)
+    {
+        Health::Healthy
+    } else {
+        Health::Unhealthy
+    }
+}
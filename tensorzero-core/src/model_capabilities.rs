@@ -0,0 +1,90 @@
+//! Per-model-provider capability metadata, used to validate requests before dispatch and to
+//! steer fallback routing away from providers that can't handle a given request.
+//!
+//! `ModelProvider` (in `model.rs`) doesn't declare what it supports today, so a function that
+//! needs tool calls or JSON mode is dispatched blindly to whichever provider is next in the
+//! fallback chain, and a provider like Bedrock Llama that rejects requests with no explicit
+//! `max_tokens` fails at the HTTP layer instead of at request-validation time. This module is
+//! the capability model and the checks built on it (`validate_capabilities`,
+//! `effective_max_tokens`, `select_capable_providers`); wiring a `capabilities:
+//! ModelCapabilities` field onto `ModelProvider` and having the `ModelProvidersExhausted`
+//! fallback loop call `select_capable_providers` before dispatch belongs in `model.rs`, which
+//! isn't present in this tree.
+
+use crate::error::{Error, ErrorDetails};
+
+/// What a given model provider is known to support.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModelCapabilities {
+    pub supports_function_calling: bool,
+    pub supports_json_mode: bool,
+    /// Set for providers (e.g. Bedrock Llama) that reject requests with no explicit
+    /// `max_tokens` rather than applying their own default.
+    pub require_max_tokens: bool,
+}
+
+/// What a particular request needs from whichever provider ends up handling it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestRequirements {
+    pub needs_function_calling: bool,
+    pub needs_json_mode: bool,
+}
+
+impl ModelCapabilities {
+    /// Whether this provider can handle a request with the given requirements.
+    pub fn supports(&self, requirements: RequestRequirements) -> bool {
+        (!requirements.needs_function_calling || self.supports_function_calling)
+            && (!requirements.needs_json_mode || self.supports_json_mode)
+    }
+}
+
+/// Rejects the request outright if `capabilities` can't satisfy `requirements`, naming the
+/// missing capability instead of letting the request fail opaquely at the provider.
+pub fn validate_capabilities(
+    provider_name: &str,
+    capabilities: ModelCapabilities,
+    requirements: RequestRequirements,
+) -> Result<(), Error> {
+    if requirements.needs_function_calling && !capabilities.supports_function_calling {
+        return Err(Error::new(ErrorDetails::UnsupportedCapability {
+            provider_name: provider_name.to_string(),
+            capability: "function calling".to_string(),
+        }));
+    }
+    if requirements.needs_json_mode && !capabilities.supports_json_mode {
+        return Err(Error::new(ErrorDetails::UnsupportedCapability {
+            provider_name: provider_name.to_string(),
+            capability: "JSON mode".to_string(),
+        }));
+    }
+    Ok(())
+}
+
+/// When the provider requires an explicit `max_tokens` and the request doesn't set one,
+/// returns `default_max_tokens` in its place, so the request doesn't fail at the provider for
+/// lacking a token cap it never needed to omit.
+pub fn effective_max_tokens(
+    capabilities: ModelCapabilities,
+    requested_max_tokens: Option<u32>,
+    default_max_tokens: Option<u32>,
+) -> Option<u32> {
+    if requested_max_tokens.is_some() || !capabilities.require_max_tokens {
+        return requested_max_tokens;
+    }
+    default_max_tokens
+}
+
+/// Filters `providers` down to those whose capabilities satisfy `requirements`, so a fallback
+/// loop can skip incompatible providers outright instead of dispatching to each in turn and
+/// failing them one by one, keeping the resulting `provider_errors` map limited to providers
+/// that were actually worth trying.
+pub fn select_capable_providers<'a, T>(
+    providers: &'a [(T, ModelCapabilities)],
+    requirements: RequestRequirements,
+) -> Vec<&'a T> {
+    providers
+        .iter()
+        .filter(|(_, capabilities)| capabilities.supports(requirements))
+        .map(|(provider, _)| provider)
+        .collect()
+}
@@ -1,6 +1,6 @@
 #![allow(clippy::unwrap_used)]
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use lazy_static::lazy_static;
@@ -12,8 +12,11 @@ use uuid::Uuid;
 
 use crate::inference::InferenceProvider;
 
+use crate::admission_control::{AdmissionControl, PermitHeldStream};
 use crate::cache::ModelProviderRequest;
-use crate::embeddings::{EmbeddingProvider, EmbeddingProviderResponse, EmbeddingRequest};
+use crate::embeddings::{
+    EmbeddingBatchProviderResponse, EmbeddingProvider, EmbeddingProviderResponse, EmbeddingRequest,
+};
 use crate::endpoints::inference::InferenceCredentials;
 use crate::error::{Error, ErrorDetails};
 use crate::inference::types::batch::PollBatchInferenceResponse;
@@ -39,22 +42,31 @@ pub struct DummyProvider {
     pub model_name: String,
     #[serde(skip)]
     pub credentials: DummyCredentials,
+    /// When set, `infer`/`infer_stream` acquire a permit before dispatching and hold it for
+    /// the lifetime of the call — for `infer_stream`, that means for the lifetime of the
+    /// returned stream, via [`PermitHeldStream`], not just until the first chunk is produced.
+    #[serde(skip)]
+    pub admission_control: Option<Arc<AdmissionControl>>,
 }
 
 impl DummyProvider {
     pub fn new(
         model_name: String,
         api_key_location: Option<CredentialLocation>,
+        max_concurrent_requests: Option<usize>,
     ) -> Result<Self, Error> {
         let api_key_location = api_key_location.unwrap_or(default_api_key_location());
+        let admission_control = max_concurrent_requests.map(|n| Arc::new(AdmissionControl::new(n)));
         match api_key_location {
             CredentialLocation::Dynamic(key_name) => Ok(DummyProvider {
                 model_name,
                 credentials: DummyCredentials::Dynamic(key_name),
+                admission_control,
             }),
             CredentialLocation::None => Ok(DummyProvider {
                 model_name,
                 credentials: DummyCredentials::None,
+                admission_control,
             }),
             _ => Err(Error::new(ErrorDetails::Config {
                 message: "Invalid api_key_location for Dummy provider".to_string(),
@@ -138,12 +150,105 @@ impl DummyProvider {
             DUMMY_RAW_REQUEST.to_string(),
         ))
     }
+
+    /// Like `create_streaming_reasoning_response`, but the post-thinking chunks are
+    /// `ToolCallChunk`s (sharing a single tool-call `id`) instead of `TextChunk`s, so tests
+    /// can exercise providers that interleave a reasoning trace with a fragmented tool-call
+    /// argument stream.
+    async fn create_streaming_reasoning_tool_response(
+        &self,
+        thinking_chunks: Vec<&'static str>,
+        tool_argument_chunks: Vec<&'static str>,
+    ) -> Result<(PeekableProviderInferenceResponseStream, String), Error> {
+        let thinking_chunks = thinking_chunks.into_iter().map(|chunk| {
+            ContentBlockChunk::Thought(ThoughtChunk {
+                text: Some(chunk.to_string()),
+                signature: None,
+                id: "0".to_string(),
+                provider_type: None,
+            })
+        });
+        let tool_call_chunks = tool_argument_chunks
+            .into_iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                ContentBlockChunk::ToolCall(ToolCallChunk {
+                    id: "0".to_string(),
+                    raw_name: if i == 0 {
+                        Some("get_temperature".to_string())
+                    } else {
+                        None
+                    },
+                    raw_arguments: chunk.to_string(),
+                })
+            });
+        let num_chunks = thinking_chunks.len() + tool_call_chunks.len();
+        let created = current_timestamp();
+        let chained = thinking_chunks.into_iter().chain(tool_call_chunks);
+        let total_tokens = num_chunks as u32;
+        let stream = tokio_stream::iter(chained.enumerate())
+            .map(move |(i, chunk)| {
+                Ok(ProviderInferenceResponseChunk {
+                    created,
+                    content: vec![chunk],
+                    usage: None,
+                    raw_response: String::new(),
+                    latency: Duration::from_millis(50 + 10 * (i as u64 + 1)),
+                    finish_reason: None,
+                })
+            })
+            .chain(tokio_stream::once(Ok(ProviderInferenceResponseChunk {
+                created,
+                content: vec![],
+                usage: Some(self.get_model_usage(total_tokens)),
+                finish_reason: Some(FinishReason::ToolCall),
+                raw_response: String::new(),
+                latency: Duration::from_millis(50 + 10 * (num_chunks as u64)),
+            })))
+            .throttle(std::time::Duration::from_millis(10));
+
+        Ok((
+            futures::stream::StreamExt::peekable(Box::pin(stream)),
+            DUMMY_RAW_REQUEST.to_string(),
+        ))
+    }
 }
 
 fn default_api_key_location() -> CredentialLocation {
     CredentialLocation::None
 }
 
+/// Number of tool-calling round-trips the `tool_loop` dummy model drives before answering:
+/// it keeps calling `get_temperature` while it has seen fewer than this many `ToolResult`s, so
+/// a test exercising it actually walks more than one step of a multi-step tool-calling loop
+/// instead of immediately resolving after the first round-trip.
+const TOOL_LOOP_STEPS: usize = 2;
+
+/// Counts the `ToolResult` content blocks already present across `messages`, so that a
+/// stateful dummy model (e.g. `tool_loop`) can tell how many steps of a multi-turn
+/// function-calling loop have already completed.
+fn count_tool_results(messages: &[crate::inference::types::RequestMessage]) -> usize {
+    messages
+        .iter()
+        .flat_map(|message| message.content.iter())
+        .filter(|block| matches!(block, ContentBlock::ToolResult(_)))
+        .count()
+}
+
+/// Returns the most recently appended `ToolResult`'s value, for models that echo it back
+/// in their final answer.
+fn last_tool_result_value(messages: &[crate::inference::types::RequestMessage]) -> String {
+    messages
+        .iter()
+        .flat_map(|message| message.content.iter())
+        .filter_map(|block| match block {
+            ContentBlock::ToolResult(tool_result) => Some(tool_result.result.clone()),
+            _ => None,
+        })
+        .next_back()
+        .unwrap_or_default()
+}
+
 #[derive(Debug, Default)]
 pub enum DummyCredentials {
     #[default]
@@ -196,6 +301,60 @@ lazy_static! {
     // Since that field is an enum, this should fail validation
     pub static ref DUMMY_BAD_TOOL_RESPONSE: Value = json!({"location": "Brooklyn", "units": "Celsius"});
     static ref FLAKY_COUNTERS: Mutex<HashMap<String, u16>> = Mutex::new(HashMap::new());
+    static ref FAULT_COUNTERS: Mutex<HashMap<String, u32>> = Mutex::new(HashMap::new());
+}
+
+/// A small fault-injection spec decoded from a `model_name` of the form
+/// `fault::status=429,after=2,latency_ms=800` (or `fault::stream_error_at=3` for the
+/// streaming path). Each field is independent and optional, so tests can compose exactly
+/// the failure mode they need instead of relying on a fixed set of sentinel model names.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct FaultSpec {
+    /// HTTP status code to attach to the injected `InferenceClient` error.
+    status: Option<u16>,
+    /// The call number (1-indexed, per distinct `model_name`) after which every call fails.
+    /// `after=2` means the first two calls succeed and every call from the third onward fails.
+    after: Option<u32>,
+    /// Extra latency (in milliseconds) to sleep before responding.
+    latency_ms: Option<u64>,
+    /// For `infer_stream`: the 0-indexed chunk at which to raise an error partway through
+    /// an otherwise-successful stream.
+    stream_error_at: Option<usize>,
+}
+
+const FAULT_PREFIX: &str = "fault::";
+
+/// Parses a `fault::key=val,key=val` model name into a [`FaultSpec`]. Returns `None` if
+/// `model_name` doesn't use the `fault::` prefix. Unknown or malformed `key=val` pairs are
+/// ignored rather than rejected, since this is a test-only harness.
+fn parse_fault_spec(model_name: &str) -> Option<FaultSpec> {
+    let rest = model_name.strip_prefix(FAULT_PREFIX)?;
+    let mut spec = FaultSpec::default();
+    for pair in rest.split(',') {
+        let Some((key, value)) = pair.split_once('=') else {
+            continue;
+        };
+        match key {
+            "status" => spec.status = value.parse().ok(),
+            "after" => spec.after = value.parse().ok(),
+            "latency_ms" => spec.latency_ms = value.parse().ok(),
+            "stream_error_at" => spec.stream_error_at = value.parse().ok(),
+            _ => {}
+        }
+    }
+    Some(spec)
+}
+
+/// Increments and returns the per-`model_name` call counter used to implement
+/// [`FaultSpec::after`] deterministically across repeated calls.
+fn next_fault_call_count(model_name: &str) -> u32 {
+    #[expect(clippy::expect_used)]
+    let mut counters = FAULT_COUNTERS
+        .lock()
+        .expect("FAULT_COUNTERS mutex is poisoned");
+    let counter = counters.entry(model_name.to_string()).or_insert(0);
+    *counter += 1;
+    *counter
 }
 pub static DUMMY_JSON_RESPONSE_RAW: &str = r#"{"answer":"Hello"}"#;
 pub static DUMMY_JSON_GOODBYE_RESPONSE_RAW: &str = r#"{"answer":"Goodbye"}"#;
@@ -246,10 +405,40 @@ impl InferenceProvider for DummyProvider {
         dynamic_api_keys: &'a InferenceCredentials,
         model_provider: &'a ModelProvider,
     ) -> Result<ProviderInferenceResponse, Error> {
+        // Held for the rest of this call (including the sleeps/HTTP-shaped work below) and
+        // released when it drops at the end of the function.
+        let _permit = self
+            .admission_control
+            .as_ref()
+            .map(|admission_control| admission_control.try_acquire())
+            .transpose()?;
+
         if self.model_name == "slow" {
             tokio::time::sleep(Duration::from_secs(5)).await;
         }
 
+        if let Some(fault) = parse_fault_spec(&self.model_name) {
+            if let Some(latency_ms) = fault.latency_ms {
+                tokio::time::sleep(Duration::from_millis(latency_ms)).await;
+            }
+            let call_count = next_fault_call_count(&self.model_name);
+            if fault.after.map_or(true, |after| call_count > after) {
+                return Err(ErrorDetails::InferenceClient {
+                    raw_request: Some("raw request".to_string()),
+                    raw_response: None,
+                    message: format!(
+                        "Injected fault for model '{}' on call number {call_count}",
+                        self.model_name
+                    ),
+                    status_code: fault
+                        .status
+                        .and_then(|s| reqwest::StatusCode::from_u16(s).ok()),
+                    provider_type: PROVIDER_TYPE.to_string(),
+                }
+                .into());
+            }
+        }
+
         // Check for flaky models
         if self.model_name.starts_with("flaky_") {
             #[expect(clippy::expect_used)]
@@ -369,6 +558,27 @@ impl InferenceProvider for DummyProvider {
                 arguments: serde_json::to_string(&*DUMMY_BAD_TOOL_RESPONSE).unwrap(),
                 id: "0".to_string(),
             })],
+            "tool_loop" => {
+                let num_tool_results = count_tool_results(&request.messages);
+                if num_tool_results < TOOL_LOOP_STEPS {
+                    vec![ContentBlockOutput::ToolCall(ToolCall {
+                        name: "get_temperature".to_string(),
+                        #[expect(clippy::unwrap_used)]
+                        arguments: serde_json::to_string(&*DUMMY_TOOL_RESPONSE).unwrap(),
+                        id: num_tool_results.to_string(),
+                    })]
+                } else {
+                    vec![ContentBlockOutput::Text(Text {
+                        text: json!({
+                            "answer": format!(
+                                "Got {num_tool_results} tool result(s), the latest was {}",
+                                last_tool_result_value(&request.messages)
+                            )
+                        })
+                        .to_string(),
+                    })]
+                }
+            }
             "json" => vec![DUMMY_JSON_RESPONSE_RAW.to_string().into()],
             "json_goodbye" => vec![DUMMY_JSON_GOODBYE_RESPONSE_RAW.to_string().into()],
             "json_cot" => vec![DUMMY_JSON_COT_RESPONSE_RAW.to_string().into()],
@@ -528,7 +738,13 @@ impl InferenceProvider for DummyProvider {
         };
         let system = request.system.clone();
         let input_messages = request.messages.clone();
-        let finish_reason = if self.model_name.contains("tool") {
+        let finish_reason = if self.model_name == "tool_loop" {
+            if count_tool_results(&request.messages) < TOOL_LOOP_STEPS {
+                Some(FinishReason::ToolCall)
+            } else {
+                Some(FinishReason::Stop)
+            }
+        } else if self.model_name.contains("tool") {
             Some(FinishReason::ToolCall)
         } else {
             Some(FinishReason::Stop)
@@ -547,7 +763,69 @@ impl InferenceProvider for DummyProvider {
         })
     }
 
+    /// Acquires an admission-control permit (if configured) and holds it for the lifetime of
+    /// the returned stream via [`PermitHeldStream`], rather than releasing it as soon as the
+    /// stream is constructed. The actual per-`model_name` response shaping happens in
+    /// [`Self::infer_stream_uncontrolled`].
     async fn infer_stream<'a>(
+        &'a self,
+        request: ModelProviderRequest<'a>,
+        http_client: &'a reqwest::Client,
+        dynamic_api_keys: &'a InferenceCredentials,
+        model_provider: &'a ModelProvider,
+    ) -> Result<(PeekableProviderInferenceResponseStream, String), Error> {
+        let permit = self
+            .admission_control
+            .as_ref()
+            .map(|admission_control| admission_control.try_acquire())
+            .transpose()?;
+        let (stream, raw_request) = self
+            .infer_stream_uncontrolled(request, http_client, dynamic_api_keys, model_provider)
+            .await?;
+        let Some(permit) = permit else {
+            return Ok((stream, raw_request));
+        };
+        let held: ProviderInferenceResponseStreamInner =
+            Box::pin(PermitHeldStream::new(stream, permit));
+        Ok((futures::stream::StreamExt::peekable(held), raw_request))
+    }
+
+    async fn start_batch_inference<'a>(
+        &'a self,
+        requests: &'a [ModelInferenceRequest<'_>],
+        _client: &'a reqwest::Client,
+        _dynamic_api_keys: &'a InferenceCredentials,
+    ) -> Result<StartBatchProviderInferenceResponse, Error> {
+        let file_id = Uuid::now_v7();
+        let batch_id = Uuid::now_v7();
+        let raw_requests: Vec<String> =
+            requests.iter().map(|_| "raw_request".to_string()).collect();
+        Ok(StartBatchProviderInferenceResponse {
+            batch_id,
+            batch_params: json!({"file_id": file_id, "batch_id": batch_id}),
+            status: BatchStatus::Pending,
+            raw_requests,
+            raw_request: "raw request".to_string(),
+            raw_response: "raw response".to_string(),
+            errors: vec![],
+        })
+    }
+
+    async fn poll_batch_inference<'a>(
+        &'a self,
+        _batch_request: &'a BatchRequestRow<'a>,
+        _http_client: &'a reqwest::Client,
+        _dynamic_api_keys: &'a InferenceCredentials,
+    ) -> Result<PollBatchInferenceResponse, Error> {
+        Err(ErrorDetails::UnsupportedModelProviderForBatchInference {
+            provider_type: "Dummy".to_string(),
+        }
+        .into())
+    }
+}
+
+impl DummyProvider {
+    async fn infer_stream_uncontrolled<'a>(
         &'a self,
         ModelProviderRequest {
             request: _,
@@ -561,6 +839,33 @@ impl InferenceProvider for DummyProvider {
         if self.model_name == "slow" {
             tokio::time::sleep(Duration::from_secs(5)).await;
         }
+
+        let mut stream_error_at: Option<usize> = None;
+        if let Some(fault) = parse_fault_spec(&self.model_name) {
+            if let Some(latency_ms) = fault.latency_ms {
+                tokio::time::sleep(Duration::from_millis(latency_ms)).await;
+            }
+            if fault.stream_error_at.is_none() {
+                let call_count = next_fault_call_count(&self.model_name);
+                if fault.after.map_or(true, |after| call_count > after) {
+                    return Err(ErrorDetails::InferenceClient {
+                        raw_request: Some("raw request".to_string()),
+                        raw_response: None,
+                        message: format!(
+                            "Injected fault for model '{}' on call number {call_count}",
+                            self.model_name
+                        ),
+                        status_code: fault
+                            .status
+                            .and_then(|s| reqwest::StatusCode::from_u16(s).ok()),
+                        provider_type: PROVIDER_TYPE.to_string(),
+                    }
+                    .into());
+                }
+            } else {
+                stream_error_at = fault.stream_error_at;
+            }
+        }
         // Check for flaky models
         if self.model_name.starts_with("flaky_") {
             #[expect(clippy::expect_used)]
@@ -606,6 +911,14 @@ impl InferenceProvider for DummyProvider {
                 .create_streaming_reasoning_response(vec![], DUMMY_STREAMING_JSON_RESPONSE.to_vec())
                 .await;
         }
+        if self.model_name == "reasoner_tool" {
+            return self
+                .create_streaming_reasoning_tool_response(
+                    DUMMY_STREAMING_THINKING.to_vec(),
+                    DUMMY_STREAMING_TOOL_RESPONSE.to_vec(),
+                )
+                .await;
+        }
 
         if self.model_name.starts_with("error") {
             return Err(ErrorDetails::InferenceClient {
@@ -622,6 +935,8 @@ impl InferenceProvider for DummyProvider {
         }
 
         let err_in_stream = self.model_name == "err_in_stream";
+        let fault_status = parse_fault_spec(&self.model_name).and_then(|fault| fault.status);
+        let model_name = self.model_name.clone();
 
         let created = current_timestamp();
 
@@ -654,6 +969,17 @@ impl InferenceProvider for DummyProvider {
                             provider_type: PROVIDER_TYPE.to_string(),
                         }));
                     }
+                    if stream_error_at == Some(i) {
+                        return Err(Error::new(ErrorDetails::InferenceClient {
+                            message: format!(
+                                "Injected fault for model '{model_name}' after {i} good chunk(s)"
+                            ),
+                            raw_request: Some("raw request".to_string()),
+                            raw_response: None,
+                            status_code: fault_status,
+                            provider_type: PROVIDER_TYPE.to_string(),
+                        }));
+                    }
                     // We want to simulate the tool name being in the first chunk, but not in the subsequent chunks.
                     let tool_name = if i == 0 && !split_tool_name {
                         Some("get_temperature".to_string())
@@ -706,40 +1032,60 @@ impl InferenceProvider for DummyProvider {
             DUMMY_RAW_REQUEST.to_string(),
         ))
     }
-
-    async fn start_batch_inference<'a>(
-        &'a self,
-        requests: &'a [ModelInferenceRequest<'_>],
-        _client: &'a reqwest::Client,
-        _dynamic_api_keys: &'a InferenceCredentials,
-    ) -> Result<StartBatchProviderInferenceResponse, Error> {
-        let file_id = Uuid::now_v7();
-        let batch_id = Uuid::now_v7();
-        let raw_requests: Vec<String> =
-            requests.iter().map(|_| "raw_request".to_string()).collect();
-        Ok(StartBatchProviderInferenceResponse {
-            batch_id,
-            batch_params: json!({"file_id": file_id, "batch_id": batch_id}),
-            status: BatchStatus::Pending,
-            raw_requests,
-            raw_request: "raw request".to_string(),
-            raw_response: "raw response".to_string(),
-            errors: vec![],
-        })
+}
+/// Lets `DummyProvider` be driven through the gateway's continuous-batching [`crate::batching::Batcher`]:
+/// each coalesced request gets its own (still independent) response, so tests can assert that
+/// `max_batch_size`/`max_waiting_ms` actually grouped concurrent calls without changing the
+/// per-request semantics used by `infer`.
+/// Keys off `model_name` to flip between healthy and unhealthy, mirroring how
+/// `err_in_stream`/`error*` sentinel models are already handled in `infer`/`infer_stream`, so
+/// the health-watcher background task and health-aware routing can be tested without a real
+/// flaky HTTP provider.
+impl crate::health::HealthProbe for DummyProvider {
+    async fn probe(&self) -> bool {
+        !(self.model_name == "unhealthy" || self.model_name.starts_with("error"))
     }
+}
 
-    async fn poll_batch_inference<'a>(
-        &'a self,
-        _batch_request: &'a BatchRequestRow<'a>,
-        _http_client: &'a reqwest::Client,
-        _dynamic_api_keys: &'a InferenceCredentials,
-    ) -> Result<PollBatchInferenceResponse, Error> {
-        Err(ErrorDetails::UnsupportedModelProviderForBatchInference {
-            provider_type: "Dummy".to_string(),
+impl crate::batching::BatchSubmit for DummyProvider {
+    async fn submit_batch(
+        &self,
+        requests: Vec<ModelInferenceRequest<'static>>,
+    ) -> Result<Vec<Result<ProviderInferenceResponse, Error>>, Error> {
+        if self.model_name.starts_with("error") {
+            return Err(ErrorDetails::InferenceClient {
+                message: format!(
+                    "Error sending request to Dummy provider for model '{}'.",
+                    self.model_name
+                ),
+                raw_request: Some("raw request".to_string()),
+                raw_response: None,
+                status_code: None,
+                provider_type: PROVIDER_TYPE.to_string(),
+            }
+            .into());
         }
-        .into())
+        let mut responses = Vec::with_capacity(requests.len());
+        for request in requests {
+            responses.push(Ok(ProviderInferenceResponse {
+                id: Uuid::now_v7(),
+                created: current_timestamp(),
+                output: vec![DUMMY_INFER_RESPONSE_CONTENT.to_string().into()],
+                raw_request: DUMMY_RAW_REQUEST.to_string(),
+                raw_response: DUMMY_INFER_RESPONSE_RAW.to_string(),
+                usage: self.get_model_usage(1),
+                latency: Latency::NonStreaming {
+                    response_time: Duration::from_millis(100),
+                },
+                system: request.system.clone(),
+                input_messages: request.messages.clone(),
+                finish_reason: Some(FinishReason::Stop),
+            }));
+        }
+        Ok(responses)
     }
 }
+
 lazy_static! {
     static ref EMPTY_SECRET: SecretString = SecretString::from(String::new());
 }
@@ -787,4 +1133,101 @@ impl EmbeddingProvider for DummyProvider {
             latency,
         })
     }
+
+    /// Embeds every input in `request.inputs` in a single call instead of requiring one
+    /// round-trip per input, the way text-embeddings-inference batches a request. Ordering
+    /// of `embeddings` matches `request.inputs`, and `usage` aggregates input tokens across
+    /// the whole batch.
+    async fn embed_batch(
+        &self,
+        request: &EmbeddingRequest,
+        _http_client: &reqwest::Client,
+        _dynamic_api_keys: &InferenceCredentials,
+    ) -> Result<EmbeddingBatchProviderResponse, Error> {
+        if self.model_name.starts_with("error") {
+            return Err(ErrorDetails::InferenceClient {
+                message: format!(
+                    "Error sending request to Dummy provider for model '{}'.",
+                    self.model_name
+                ),
+                raw_request: Some("raw request".to_string()),
+                raw_response: None,
+                status_code: None,
+                provider_type: PROVIDER_TYPE.to_string(),
+            }
+            .into());
+        }
+        let created = current_timestamp();
+        let embeddings: Vec<Vec<f32>> = request.inputs.iter().map(|_| vec![0.0; 1536]).collect();
+        let usage = Usage {
+            input_tokens: 10 * embeddings.len() as u32,
+            output_tokens: embeddings.len() as u32,
+        };
+        Ok(EmbeddingBatchProviderResponse {
+            id: Uuid::now_v7(),
+            inputs: request.inputs.clone(),
+            embeddings,
+            created,
+            raw_request: DUMMY_RAW_REQUEST.to_string(),
+            raw_response: DUMMY_RAW_REQUEST.to_string(),
+            usage,
+            latency: Latency::NonStreaming {
+                response_time: Duration::from_millis(100),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_threads_max_concurrent_requests_into_admission_control() {
+        let provider =
+            DummyProvider::new("good".to_string(), None, Some(1)).expect("should construct");
+        let admission_control = provider
+            .admission_control
+            .expect("admission_control should be Some when max_concurrent_requests is set");
+        let _permit = admission_control
+            .try_acquire()
+            .expect("first acquire should succeed");
+        let err = admission_control
+            .try_acquire()
+            .expect_err("second acquire should fail while the first permit is held");
+        assert!(matches!(err.get_details(), ErrorDetails::Overloaded));
+    }
+
+    #[test]
+    fn test_new_leaves_admission_control_unset_by_default() {
+        let provider =
+            DummyProvider::new("good".to_string(), None, None).expect("should construct");
+        assert!(provider.admission_control.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_permit_held_stream_holds_the_permit_for_the_stream_lifetime() {
+        let admission_control = Arc::new(AdmissionControl::new(1));
+        let _first_permit = admission_control
+            .try_acquire()
+            .expect("first acquire should succeed");
+
+        // Simulate `infer_stream` wrapping an in-flight provider stream in a `PermitHeldStream`:
+        // the permit moves into the stream instead of being released once the stream is built.
+        let inner = tokio_stream::once(1);
+        let mut held = PermitHeldStream::new(inner, _first_permit);
+
+        let err = admission_control
+            .try_acquire()
+            .expect_err("acquire should fail while the stream still holds the permit");
+        assert!(matches!(err.get_details(), ErrorDetails::Overloaded));
+
+        // Draining and dropping the stream releases the permit.
+        assert_eq!(held.next().await, Some(1));
+        drop(held);
+
+        admission_control
+            .try_acquire()
+            .expect("acquire should succeed once the stream has dropped its permit");
+    }
 }
@@ -0,0 +1,167 @@
+//! Reassembles the fragments a provider streams for a single tool call or a JSON-mode response
+//! into a complete buffer, and reports whether that buffer is syntactically complete and
+//! schema-valid, instead of leaving callers to re-parse the whole stream's content by hand once
+//! it ends.
+//!
+//! Providers split a single tool call's arguments (or a single JSON response) across many
+//! chunks — see `DummyProvider::create_streaming_reasoning_tool_response` for the shape this
+//! reassembles: repeated `ToolCallChunk`s that share one `id`, with `raw_name` only set on the
+//! first chunk. [`ToolCallAggregator`] folds those deltas back into whole [`ToolCall`]s, and
+//! [`finish_json_aggregation`] does the equivalent for a JSON-mode buffer, checking it against
+//! whatever validator the caller already has on hand (a `StaticJSONSchema` or a
+//! `DynamicJSONSchema`, both of which this module deliberately doesn't depend on directly).
+//!
+//! Actually wiring this into the `InferenceResultStream` that `Variant::infer_stream` returns —
+//! so a consumer sees a single typed terminal event instead of having to call
+//! `finish_json_aggregation` themselves once the stream ends — belongs in `inference/types.rs`,
+//! which defines `InferenceResultStream` and its per-chunk item type, and isn't present in this
+//! tree.
+
+use crate::error::Error;
+use crate::tool::{ToolCall, ToolCallChunk};
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    name: Option<String>,
+    raw_arguments: String,
+}
+
+/// Reassembles a stream of (possibly interleaved, by `id`) tool-call argument deltas into
+/// complete [`ToolCall`]s, preserving the order each `id` was first seen in.
+#[derive(Debug, Default)]
+pub struct ToolCallAggregator {
+    order: Vec<String>,
+    partials: HashMap<String, PartialToolCall>,
+}
+
+impl ToolCallAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Folds one chunk's tool-call delta into the running buffer for its `id`.
+    pub fn push(&mut self, chunk: &ToolCallChunk) {
+        if !self.partials.contains_key(&chunk.id) {
+            self.order.push(chunk.id.clone());
+        }
+        let partial = self.partials.entry(chunk.id.clone()).or_default();
+        if let Some(name) = &chunk.raw_name {
+            partial.name = Some(name.clone());
+        }
+        partial.raw_arguments.push_str(&chunk.raw_arguments);
+    }
+
+    /// Returns every tool call reassembled so far. Calling this before the underlying stream has
+    /// finished is fine — it just returns each tool call's arguments as concatenated up to now,
+    /// which may not yet be syntactically complete.
+    pub fn finish(self) -> Vec<ToolCall> {
+        self.order
+            .into_iter()
+            .filter_map(|id| {
+                let partial = self.partials.get(&id)?;
+                Some(ToolCall {
+                    id: id.clone(),
+                    name: partial.name.clone().unwrap_or_default(),
+                    arguments: partial.raw_arguments.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The terminal event for a buffer (tool-call arguments or a JSON-mode response) reassembled
+/// from stream chunks, once the underlying stream ends.
+#[derive(Debug, Clone)]
+pub enum StreamAggregationOutcome {
+    /// The buffer parsed as JSON and satisfied the schema.
+    Parsed(serde_json::Value),
+    /// The buffer never became valid JSON — e.g. the provider's stream ended before finishing a
+    /// value, or it isn't producing JSON at all. Mirrors the `parsed: None` semantics already
+    /// used for a non-streaming response whose raw output didn't parse.
+    Raw(String),
+    /// The buffer parsed as JSON but failed schema validation; `raw` is kept so the caller isn't
+    /// left with nothing to show for a stream that otherwise completed normally.
+    SchemaError { raw: String, error: Error },
+}
+
+/// Parses `raw` as JSON and checks it with `validate`, returning the appropriate
+/// [`StreamAggregationOutcome`]. Takes a validation closure rather than a schema type directly so
+/// this module doesn't need to depend on `StaticJSONSchema`/`DynamicJSONSchema`'s exact API —
+/// callers already have one of those on hand and know how to validate with it.
+pub fn finish_json_aggregation(
+    raw: String,
+    validate: impl FnOnce(&serde_json::Value) -> Result<(), Error>,
+) -> StreamAggregationOutcome {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&raw) else {
+        return StreamAggregationOutcome::Raw(raw);
+    };
+    match validate(&value) {
+        Ok(()) => StreamAggregationOutcome::Parsed(value),
+        Err(error) => StreamAggregationOutcome::SchemaError { raw, error },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorDetails;
+
+    fn tool_call_chunk(id: &str, raw_name: Option<&str>, raw_arguments: &str) -> ToolCallChunk {
+        ToolCallChunk {
+            id: id.to_string(),
+            raw_name: raw_name.map(str::to_string),
+            raw_arguments: raw_arguments.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tool_call_aggregator_reassembles_fragmented_arguments() {
+        let mut aggregator = ToolCallAggregator::new();
+        aggregator.push(&tool_call_chunk("0", Some("get_temperature"), "{\"loc"));
+        aggregator.push(&tool_call_chunk("0", None, "ation\":\"Tokyo\"}"));
+        let tool_calls = aggregator.finish();
+        assert_eq!(tool_calls.len(), 1);
+        assert_eq!(tool_calls[0].name, "get_temperature");
+        assert_eq!(tool_calls[0].arguments, "{\"location\":\"Tokyo\"}");
+    }
+
+    #[test]
+    fn test_tool_call_aggregator_keeps_interleaved_calls_separate() {
+        let mut aggregator = ToolCallAggregator::new();
+        aggregator.push(&tool_call_chunk("0", Some("get_temperature"), "{}"));
+        aggregator.push(&tool_call_chunk("1", Some("get_humidity"), "{}"));
+        let tool_calls = aggregator.finish();
+        assert_eq!(tool_calls.len(), 2);
+        assert_eq!(tool_calls[0].id, "0");
+        assert_eq!(tool_calls[1].id, "1");
+    }
+
+    #[test]
+    fn test_finish_json_aggregation_parsed() {
+        let outcome = finish_json_aggregation("{\"answer\": \"hi\"}".to_string(), |_value| Ok(()));
+        assert!(matches!(outcome, StreamAggregationOutcome::Parsed(_)));
+    }
+
+    #[test]
+    fn test_finish_json_aggregation_raw_when_incomplete() {
+        let outcome = finish_json_aggregation("{\"answer\": \"h".to_string(), |_value| Ok(()));
+        assert!(matches!(outcome, StreamAggregationOutcome::Raw(_)));
+    }
+
+    #[test]
+    fn test_finish_json_aggregation_schema_error_keeps_raw() {
+        let raw = "{\"answer\": \"hi\"}".to_string();
+        let outcome = finish_json_aggregation(raw.clone(), |_value| {
+            Err(Error::new(ErrorDetails::Config {
+                message: "missing required field".to_string(),
+            }))
+        });
+        match outcome {
+            StreamAggregationOutcome::SchemaError {
+                raw: returned_raw, ..
+            } => assert_eq!(returned_raw, raw),
+            other => panic!("expected SchemaError, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Caches deterministic tool-execution results, so the tool-calling loop in
+//! `variant::chat_completion` can skip re-invoking a side-effect-free tool it has already
+//! called with the same arguments — mirroring the `CacheOptions`/`extra_cache_key` machinery
+//! already used for model inference, but keyed by `(tool_name, raw_arguments)` with a
+//! per-tool-call opt-in TTL instead of a blanket cache policy.
+//!
+//! This module only holds the cache key logic and the in-memory store itself. Threading an
+//! instance through `InferenceClients` so it's shared across a request (the way the inference
+//! cache is) belongs in `endpoints::inference`, which isn't present in this tree; likewise,
+//! flagging a cache hit in `model_inference_results`/`usage_considering_cached` so it's visible
+//! in observability requires fields on those types that this crate snapshot doesn't have.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ToolCallKey {
+    tool_name: String,
+    raw_arguments: String,
+}
+
+struct CachedResult {
+    result: String,
+    expires_at: Instant,
+}
+
+/// An in-memory, process-local cache of tool-execution results, keyed by `(tool_name,
+/// raw_arguments)`. Each entry expires after the TTL it was inserted with, so a tool call that's
+/// no longer safe to reuse just needs its caller to stop passing a TTL rather than evict it.
+#[derive(Default)]
+pub struct ToolResultCache {
+    entries: Mutex<HashMap<ToolCallKey, CachedResult>>,
+}
+
+impl ToolResultCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached result for `(tool_name, raw_arguments)`, if present and not expired.
+    pub fn get(&self, tool_name: &str, raw_arguments: &str) -> Option<String> {
+        let key = ToolCallKey {
+            tool_name: tool_name.to_string(),
+            raw_arguments: raw_arguments.to_string(),
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(&key) {
+            Some(cached) if cached.expires_at > Instant::now() => Some(cached.result.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores `result` for `(tool_name, raw_arguments)`, valid for `ttl` from now.
+    pub fn insert(&self, tool_name: &str, raw_arguments: &str, result: String, ttl: Duration) {
+        let key = ToolCallKey {
+            tool_name: tool_name.to_string(),
+            raw_arguments: raw_arguments.to_string(),
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(
+            key,
+            CachedResult {
+                result,
+                expires_at: Instant::now() + ttl,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn test_cache_hit_and_miss() {
+        let cache = ToolResultCache::new();
+        assert_eq!(cache.get("get_weather", "{}"), None);
+        cache.insert(
+            "get_weather",
+            "{}",
+            "72F".to_string(),
+            Duration::from_secs(60),
+        );
+        assert_eq!(cache.get("get_weather", "{}").as_deref(), Some("72F"));
+        // A different argument string is a different call, even for the same tool.
+        assert_eq!(cache.get("get_weather", "{\"city\":\"nyc\"}"), None);
+    }
+
+    #[test]
+    fn test_cache_entry_expires() {
+        let cache = ToolResultCache::new();
+        cache.insert(
+            "get_weather",
+            "{}",
+            "72F".to_string(),
+            Duration::from_millis(10),
+        );
+        sleep(Duration::from_millis(30));
+        assert_eq!(cache.get("get_weather", "{}"), None);
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -17,11 +18,13 @@ use crate::inference::types::{
     ModelInferenceRequest, RequestMessage, Role,
 };
 use crate::inference::types::{
-    InferenceResult, ModelInput, ResolvedInput, ResolvedInputMessage, ResolvedInputMessageContent,
+    ContentBlockChatOutput, InferenceResult, ModelInput, ResolvedInput, ResolvedInputMessage,
+    ResolvedInputMessageContent, Usage,
 };
 use crate::jsonschema_util::StaticJSONSchema;
 use crate::minijinja_util::TemplateConfig;
 use crate::model::ModelTable;
+use crate::tool::{Tool, ToolCall, ToolResult};
 use crate::variant::JsonMode;
 
 use super::{
@@ -38,6 +41,25 @@ pub struct ChatCompletionConfig {
     pub system_template: Option<PathWithContents>,
     pub user_template: Option<PathWithContents>,
     pub assistant_template: Option<PathWithContents>,
+    /// A single HuggingFace-style template rendered once over the whole conversation, as an
+    /// alternative to the per-role `{system,user,assistant}_template`s above. When set, it takes
+    /// over entirely: the per-role templates/schemas are not used, and the rendered string is
+    /// sent to the model provider as a single text message instead of one per role. This is
+    /// meant for self-hosted models that ship their own canonical chat template, so TensorZero
+    /// can reproduce their exact prompt formatting rather than approximating it.
+    pub chat_template: Option<PathWithContents>,
+    /// Fill-in-the-middle mode: when set, `infer` bypasses the per-role templates (and
+    /// `chat_template`) and instead expects the input's user message to carry `{prefix, suffix}`,
+    /// assembling them into a single sentinel-token prompt for code-completion-style models.
+    pub fim: Option<FimConfig>,
+    /// HTTP endpoints used to actually execute tool calls, keyed by tool name. When set,
+    /// `infer` drives a multi-step loop instead of a single model call: each step's tool calls
+    /// are POSTed to the matching executor, the JSON response is fed back as the tool result,
+    /// and the model is re-invoked, up to `max_tool_steps` times.
+    pub tool_executors: Option<HashMap<String, ToolExecutorConfig>>,
+    /// The maximum number of model calls the tool-calling loop will make before returning the
+    /// last step's result as-is. Only consulted when `tool_executors` is set.
+    pub max_tool_steps: usize,
     pub temperature: Option<f32>,
     pub top_p: Option<f32>,
     pub max_tokens: Option<u32>,
@@ -46,9 +68,51 @@ pub struct ChatCompletionConfig {
     pub seed: Option<u32>,
     pub stop_sequences: Option<Vec<String>>,
     pub json_mode: Option<JsonMode>, // Only for JSON functions, not for chat functions
+    /// When set to `N > 1`, `infer` issues `N` independent generations for the same input
+    /// (varying the seed per candidate when `seed` is configured, otherwise relying on
+    /// `temperature`/`top_p` for variation) and returns the single highest-ranked one instead of
+    /// the first. See [`select_best_candidate_index`] for the ranking used.
+    pub best_of: Option<usize>,
     pub retries: RetryConfig,
     pub extra_body: Option<ExtraBodyConfig>,
     pub extra_headers: Option<ExtraHeadersConfig>,
+    /// Special tokens exposed to templates as `bos_token`/`eos_token`, so provider-accurate
+    /// chat templates (e.g. Llama/Mistral-style prompts) can be authored directly in
+    /// TensorZero's template files instead of being pre-formatted by application code.
+    pub bos_token: Option<String>,
+    pub eos_token: Option<String>,
+}
+
+/// An HTTP endpoint the gateway calls to actually execute a tool, keyed by the tool's name.
+/// The gateway POSTs the tool call's (JSON) arguments as the request body and feeds the
+/// response body back to the model as the tool result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[cfg_attr(test, ts(export))]
+pub struct ToolExecutorConfig {
+    pub url: String,
+    /// If set, a result for a given `(tool_name, raw_arguments)` pair is cached for this many
+    /// seconds and reused instead of re-invoking the executor. Only set this for tools that are
+    /// deterministic and side-effect-free (e.g. a weather lookup) — it's opt-in per tool rather
+    /// than a blanket policy because most tools aren't safe to replay blindly.
+    #[serde(default)]
+    pub cache_ttl_seconds: Option<u64>,
+}
+
+/// Sentinel tokens used to frame a fill-in-the-middle prompt as
+/// `{prefix_token}{prefix}{suffix_token}{suffix}{middle_token}`, matching the format code models
+/// like Codex/StarCoder/DeepSeek-Coder expect. Plain data with no paths to resolve, so (like
+/// [`RetryConfig`]) the same type is used for both the uninitialized and loaded variant config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[cfg_attr(test, ts(export))]
+pub struct FimConfig {
+    pub prefix_token: String,
+    pub suffix_token: String,
+    pub middle_token: String,
+    /// Appended as a default stop sequence when the variant doesn't already configure one, so
+    /// generation halts at the model's end-of-fill token instead of running on past it.
+    pub stop_token: Option<String>,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -60,6 +124,14 @@ pub struct UninitializedChatCompletionConfig {
     pub system_template: Option<TomlRelativePath>,
     pub user_template: Option<TomlRelativePath>,
     pub assistant_template: Option<TomlRelativePath>,
+    #[serde(default)]
+    pub chat_template: Option<TomlRelativePath>,
+    #[serde(default)]
+    pub fim: Option<FimConfig>,
+    #[serde(default)]
+    pub tool_executors: Option<HashMap<String, ToolExecutorConfig>>,
+    #[serde(default = "default_max_tool_steps")]
+    pub max_tool_steps: usize,
     pub temperature: Option<f32>,
     pub top_p: Option<f32>,
     pub max_tokens: Option<u32>,
@@ -70,11 +142,21 @@ pub struct UninitializedChatCompletionConfig {
     #[serde(default)]
     pub json_mode: Option<JsonMode>, // Only for JSON functions, not for chat functions
     #[serde(default)]
+    pub best_of: Option<usize>,
+    #[serde(default)]
     pub retries: RetryConfig,
     #[serde(default)]
     pub extra_body: Option<ExtraBodyConfig>,
     #[serde(default)]
     pub extra_headers: Option<ExtraHeadersConfig>,
+    #[serde(default)]
+    pub bos_token: Option<String>,
+    #[serde(default)]
+    pub eos_token: Option<String>,
+}
+
+fn default_max_tool_steps() -> usize {
+    1
 }
 
 impl LoadableConfig<ChatCompletionConfig> for UninitializedChatCompletionConfig {
@@ -94,6 +176,13 @@ impl LoadableConfig<ChatCompletionConfig> for UninitializedChatCompletionConfig
                 .assistant_template
                 .map(|path| PathWithContents::from_path(path, Some(&base_path)))
                 .transpose()?,
+            chat_template: self
+                .chat_template
+                .map(|path| PathWithContents::from_path(path, Some(&base_path)))
+                .transpose()?,
+            fim: self.fim,
+            tool_executors: self.tool_executors,
+            max_tool_steps: self.max_tool_steps,
             temperature: self.temperature,
             top_p: self.top_p,
             max_tokens: self.max_tokens,
@@ -102,9 +191,12 @@ impl LoadableConfig<ChatCompletionConfig> for UninitializedChatCompletionConfig
             seed: self.seed,
             stop_sequences: self.stop_sequences,
             json_mode: self.json_mode,
+            best_of: self.best_of,
             retries: self.retries,
             extra_body: self.extra_body,
             extra_headers: self.extra_headers,
+            bos_token: self.bos_token,
+            eos_token: self.eos_token,
         })
     }
 }
@@ -127,7 +219,13 @@ impl ChatCompletionConfig {
                 .ok_or_else(|| Error::new(ErrorDetails::InvalidTemplatePath))
         })
         .transpose()?;
-        prepare_request_message(message, templates, template_path, template_schema_info)
+        prepare_request_message(
+            message,
+            templates,
+            template_path,
+            template_schema_info,
+            self.special_tokens(),
+        )
     }
 
     pub fn prepare_system_message(
@@ -146,7 +244,114 @@ impl ChatCompletionConfig {
                     .ok_or_else(|| Error::new(ErrorDetails::InvalidTemplatePath))
             })
             .transpose()?;
-        prepare_system_message(system, templates, template_path, template_schema_info)
+        prepare_system_message(
+            system,
+            templates,
+            template_path,
+            template_schema_info,
+            self.special_tokens(),
+        )
+    }
+
+    /// The special tokens to expose to this variant's (no-schema) template context, sourced
+    /// from `self.bos_token`/`self.eos_token`.
+    fn special_tokens(&self) -> SpecialTokens<'_> {
+        SpecialTokens {
+            bos_token: self.bos_token.as_deref(),
+            eos_token: self.eos_token.as_deref(),
+        }
+    }
+
+    /// The names of shared partial templates referenced (via `{% include %}`/`{% import %}`)
+    /// by this variant's system/user/assistant templates.
+    ///
+    /// This is a building block towards having `get_all_template_paths` report the full
+    /// transitive closure of files a variant depends on, not just its three named templates, so
+    /// config export and change-detection see shared partials too. The remaining pieces —
+    /// resolving a partial name to a loaded `PathWithContents` (so it can be rebased against a
+    /// configurable template root and included in that `Vec`) — belong in `minijinja_util` and
+    /// `config_parser`, which this crate doesn't touch here.
+    pub fn referenced_partial_names(&self) -> Vec<String> {
+        self.get_all_template_paths()
+            .into_iter()
+            .flat_map(|template| parse_referenced_partials(&template.contents))
+            .collect()
+    }
+
+    /// Renders `chat_template` once over the whole conversation, HuggingFace-style, instead of
+    /// templating each message independently. The template context exposes `messages` (each as
+    /// `{role, content}`, in order), the resolved `system` value, `bos_token`/`eos_token`, and
+    /// `add_generation_prompt` (always `true` — the rendered prompt is always followed by a model
+    /// response, never used to replay a full transcript), so variant authors can reproduce a
+    /// model's own chat template rather than approximating it with three separate ones.
+    ///
+    /// Only plain text message content is supported: a message containing a tool call/result or
+    /// file would need the template to know how to format it, and this mode has no schema to
+    /// describe that shape, so such content is rejected with `ErrorDetails::InvalidMessage`.
+    fn render_whole_conversation(
+        &self,
+        chat_template: &PathWithContents,
+        templates: &TemplateConfig,
+        input: &ResolvedInput,
+    ) -> Result<String, Error> {
+        let template_path = chat_template
+            .path
+            .path()
+            .to_str()
+            .ok_or_else(|| Error::new(ErrorDetails::InvalidTemplatePath))?;
+        let messages = input
+            .messages
+            .iter()
+            .map(|message| {
+                Ok::<_, Error>(serde_json::json!({
+                    "role": match message.role {
+                        Role::User => "user",
+                        Role::Assistant => "assistant",
+                    },
+                    "content": plain_text_content(message)?,
+                }))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let special_tokens = self.special_tokens();
+        let context = serde_json::json!({
+            "messages": messages,
+            "system": input.system.clone().unwrap_or(Value::Null),
+            "bos_token": special_tokens.bos_token,
+            "eos_token": special_tokens.eos_token,
+            "add_generation_prompt": true,
+        });
+        templates.template_message(template_path, &context)
+    }
+
+    /// Renders `chat_template` against a minimal sample conversation, catching templates that
+    /// fail to render (e.g. reference an undefined filter) at config-validation time instead of
+    /// on the first real inference request. The per-role template/schema validation below is
+    /// skipped when `chat_template` is set, since this mode doesn't use it.
+    fn validate_chat_template(
+        &self,
+        chat_template: &PathWithContents,
+        templates: &TemplateConfig,
+    ) -> Result<(), Error> {
+        let template_path = chat_template
+            .path
+            .path()
+            .to_str()
+            .ok_or_else(|| Error::new(ErrorDetails::InvalidTemplatePath))?;
+        let sample_context = serde_json::json!({
+            "messages": [{"role": "user", "content": "example"}],
+            "system": Value::Null,
+            "bos_token": Value::Null,
+            "eos_token": Value::Null,
+            "add_generation_prompt": true,
+        });
+        templates
+            .template_message(template_path, &sample_context)
+            .map(|_| ())
+            .map_err(|e| {
+                Error::new(ErrorDetails::Config {
+                    message: format!("template failed to render with a sample conversation: {e}"),
+                })
+            })
     }
 
     fn prepare_request<'a, 'request>(
@@ -157,22 +362,53 @@ impl ChatCompletionConfig {
         stream: bool,
         inference_params: &mut InferenceParams,
     ) -> Result<ModelInferenceRequest<'request>, Error> {
-        let messages = input
-            .messages
-            .iter()
-            .map(|message| {
-                self.prepare_request_message(
-                    inference_config.templates,
-                    message,
-                    function.template_schema_info(),
-                )
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        let system = self.prepare_system_message(
-            inference_config.templates,
-            input.system.as_ref(),
-            function.template_schema_info(),
-        )?;
+        let (messages, system) = if let Some(fim) = &self.fim {
+            let rendered = render_fim_prompt(fim, input)?;
+            (
+                vec![RequestMessage {
+                    role: Role::User,
+                    content: vec![rendered.into()],
+                }],
+                None,
+            )
+        } else if let Some(chat_template) = &self.chat_template {
+            let rendered =
+                self.render_whole_conversation(chat_template, inference_config.templates, input)?;
+            (
+                vec![RequestMessage {
+                    role: Role::User,
+                    content: vec![rendered.into()],
+                }],
+                None,
+            )
+        } else {
+            let messages = input
+                .messages
+                .iter()
+                .map(|message| {
+                    self.prepare_request_message(
+                        inference_config.templates,
+                        message,
+                        function.template_schema_info(),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let system = self.prepare_system_message(
+                inference_config.templates,
+                input.system.as_ref(),
+                function.template_schema_info(),
+            )?;
+            (messages, system)
+        };
+
+        // `fim.stop_token` only kicks in as a default when the variant doesn't already
+        // configure its own `stop_sequences`.
+        let stop_sequences = self.stop_sequences.clone().or_else(|| {
+            self.fim
+                .as_ref()
+                .and_then(|fim| fim.stop_token.clone())
+                .map(|stop_token| vec![stop_token])
+        });
 
         inference_params
             .chat_completion
@@ -183,7 +419,7 @@ impl ChatCompletionConfig {
                 self.top_p,
                 self.presence_penalty,
                 self.frequency_penalty,
-                self.stop_sequences.clone(),
+                stop_sequences,
             );
 
         let extra_body = FullExtraBodyConfig {
@@ -216,6 +452,104 @@ impl ChatCompletionConfig {
             extra_headers,
         )
     }
+
+    /// Issues `best_of` independent generations for the same input and returns the single
+    /// highest-ranked one, per [`select_best_candidate_index`]. The seed is varied per candidate
+    /// (by adding the candidate's index) when `self.seed` is configured; otherwise the
+    /// candidates rely on `temperature`/`top_p` to actually differ, the same as repeating a call
+    /// without a seed would.
+    ///
+    /// `Usage` on the returned result is patched to reflect the whole fan-out rather than just
+    /// the winning candidate: input tokens are counted once (every candidate was prompted with
+    /// the same input), but output tokens are summed across every candidate that was sampled, so
+    /// downstream billing isn't underestimated.
+    async fn infer_best_of<'a: 'request, 'request>(
+        &'a self,
+        input: &ResolvedInput,
+        models: &'request InferenceModels<'a>,
+        function: &'a FunctionConfig,
+        inference_config: &'request InferenceConfig<'static, 'request>,
+        clients: &'request InferenceClients<'request>,
+        inference_params: InferenceParams,
+        best_of: usize,
+    ) -> Result<InferenceResult, Error> {
+        let model_config = models.models.get(&self.model).await?.ok_or_else(|| {
+            Error::new(ErrorDetails::UnknownModel {
+                name: self.model.to_string(),
+            })
+        })?;
+
+        let candidates: Vec<InferenceResult> =
+            futures::future::try_join_all((0..best_of).map(|candidate_index| {
+                let model_config = &model_config;
+                let mut candidate_params = inference_params.clone();
+                async move {
+                    let mut request = self.prepare_request(
+                        input,
+                        function,
+                        inference_config,
+                        false,
+                        &mut candidate_params,
+                    )?;
+                    if let Some(base_seed) = self.seed {
+                        request.seed = Some(base_seed.wrapping_add(candidate_index as u32));
+                    }
+                    let args = InferModelRequestArgs {
+                        request,
+                        model_name: self.model.clone(),
+                        model_config,
+                        function,
+                        inference_config,
+                        clients,
+                        inference_params: candidate_params,
+                        retry_config: &self.retries,
+                    };
+                    infer_model_request(args).await
+                }
+            }))
+            .await?;
+
+        let best_index = select_best_candidate_index(&candidates);
+        let total_output_tokens: u32 = candidates
+            .iter()
+            .map(|candidate| result_usage(candidate).output_tokens)
+            .sum();
+        let input_tokens = result_usage(&candidates[best_index]).input_tokens;
+
+        let mut best = candidates.into_iter().nth(best_index).ok_or_else(|| {
+            Error::new(ErrorDetails::Config {
+                message: "`best_of` produced no candidates".to_string(),
+            })
+        })?;
+        set_result_usage(
+            &mut best,
+            Usage {
+                input_tokens,
+                output_tokens: total_output_tokens,
+            },
+        );
+        Ok(best)
+    }
+}
+
+/// Ranks `candidates` and returns the index of the best one. Ranking by summed per-token
+/// logprobs (the default OpenAI-style `best_of` behavior) would need a logprobs field on
+/// `ChatInferenceResult`/`JsonInferenceResult` that this crate snapshot doesn't have, so this
+/// falls back straight to the other documented tiebreak: total output length, measured in output
+/// tokens. Ties favor the earliest candidate, matching the order generations were requested in.
+fn select_best_candidate_index(candidates: &[InferenceResult]) -> usize {
+    candidates
+        .iter()
+        .enumerate()
+        .fold((0, 0u32), |(best_index, best_score), (index, candidate)| {
+            let score = result_usage(candidate).output_tokens;
+            if score > best_score {
+                (index, score)
+            } else {
+                (best_index, best_score)
+            }
+        })
+        .0
 }
 
 /// Prepare a ModelInput using the same machinery as is used by core TensorZero to prepare
@@ -234,6 +568,7 @@ pub fn prepare_model_input(
         templates,
         system_template_name,
         template_schema_info,
+        SpecialTokens::default(),
     )?;
     let mut templated_messages = Vec::with_capacity(messages.len());
     for message in messages.iter() {
@@ -246,6 +581,7 @@ pub fn prepare_model_input(
             templates,
             template_name,
             template_schema_info,
+            SpecialTokens::default(),
         )?);
     }
     Ok(ModelInput {
@@ -259,6 +595,7 @@ fn prepare_system_message(
     templates: &TemplateConfig,
     template_name: Option<&str>,
     template_schema_info: TemplateSchemaInfo,
+    special_tokens: SpecialTokens<'_>,
 ) -> Result<Option<String>, Error> {
     Ok(match template_name {
         Some(template_path) => {
@@ -266,7 +603,9 @@ fn prepare_system_message(
                 Cow::Borrowed(system.unwrap_or(&Value::Null))
             } else {
                 Cow::Owned(serde_json::json!({
-                    SYSTEM_TEXT_TEMPLATE_VAR: system.unwrap_or(&Value::Null)
+                    SYSTEM_TEXT_TEMPLATE_VAR: system.unwrap_or(&Value::Null),
+                    "bos_token": special_tokens.bos_token,
+                    "eos_token": special_tokens.eos_token,
                 }))
             };
             Some(templates.template_message(
@@ -289,11 +628,253 @@ fn prepare_system_message(
     }})
 }
 
+/// Concatenates a message's plain text content, for use as the `content` field of a
+/// `ChatCompletionConfig::chat_template` message. Errors if the message contains anything that
+/// isn't renderable as plain text (tool calls/results, files, etc.), since `chat_template` has no
+/// schema to tell it how such content should be formatted.
+fn plain_text_content(message: &ResolvedInputMessage) -> Result<String, Error> {
+    message
+        .content
+        .iter()
+        .map(|block| match block {
+            ResolvedInputMessageContent::Text { value } => {
+                value.as_str().map(str::to_string).ok_or_else(|| {
+                    Error::new(ErrorDetails::InvalidMessage {
+                        message: format!(
+                            "Request message content {value} is not a string, but `chat_template` only supports plain text content for Role {}",
+                            message.role
+                        ),
+                    })
+                })
+            }
+            ResolvedInputMessageContent::RawText { value } => Ok(value.clone()),
+            other => Err(Error::new(ErrorDetails::InvalidMessage {
+                message: format!(
+                    "`chat_template` only supports plain text message content; got {other:?} for Role {}",
+                    message.role
+                ),
+            })),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|parts| parts.join(""))
+}
+
+/// Assembles a fill-in-the-middle prompt from the `{prefix, suffix}` object carried by `input`'s
+/// (single) user message, as `{prefix_token}{prefix}{suffix_token}{suffix}{middle_token}`. This
+/// bypasses templating entirely: FIM requests are framed around surrounding code, not a dialog,
+/// so there's no schema/template to apply, only sentinel tokens to assemble around the given
+/// strings.
+fn render_fim_prompt(fim: &FimConfig, input: &ResolvedInput) -> Result<String, Error> {
+    let user_message = input
+        .messages
+        .iter()
+        .rev()
+        .find(|message| matches!(message.role, Role::User))
+        .ok_or_else(|| {
+            Error::new(ErrorDetails::InvalidMessage {
+                message: "`fim` mode requires a user message carrying `prefix`/`suffix`"
+                    .to_string(),
+            })
+        })?;
+    let fields = match user_message.content.as_slice() {
+        [ResolvedInputMessageContent::Text { value }] => value,
+        _ => {
+            return Err(Error::new(ErrorDetails::InvalidMessage {
+                message: "`fim` mode requires the user message to be a single Text content block carrying `{prefix, suffix}`".to_string(),
+            }));
+        }
+    };
+    let fim_field = |name: &str| -> Result<&str, Error> {
+        fields.get(name).and_then(Value::as_str).ok_or_else(|| {
+            Error::new(ErrorDetails::InvalidMessage {
+                message: format!(
+                    "`fim` mode requires a string `{name}` field on the user message, got {fields}"
+                ),
+            })
+        })
+    };
+    let prefix = fim_field("prefix")?;
+    let suffix = fim_field("suffix")?;
+    Ok(format!(
+        "{}{}{}{}{}",
+        fim.prefix_token, prefix, fim.suffix_token, suffix, fim.middle_token
+    ))
+}
+
+/// The `Usage` accumulated by a single step of the tool-calling loop.
+fn result_usage(result: &InferenceResult) -> Usage {
+    match result {
+        InferenceResult::Chat(chat) => chat.usage.clone(),
+        InferenceResult::Json(json) => json.usage.clone(),
+    }
+}
+
+/// Overwrites a result's `Usage` with the loop's running total, so the final `InferenceResult`
+/// reports usage across every step, not just its own.
+fn set_result_usage(result: &mut InferenceResult, usage: Usage) {
+    match result {
+        InferenceResult::Chat(chat) => chat.usage = usage,
+        InferenceResult::Json(json) => json.usage = usage,
+    }
+}
+
+fn sum_usage(a: Usage, b: Usage) -> Usage {
+    Usage {
+        input_tokens: a.input_tokens + b.input_tokens,
+        output_tokens: a.output_tokens + b.output_tokens,
+    }
+}
+
+/// Pulls the tool calls out of a step's result, if any. `InferenceResult::Json` never contains
+/// tool calls, since JSON-mode functions resolve directly to their output schema.
+///
+/// `pub(crate)` so other tool-executing variants (e.g. `AgenticToolLoopConfig`) can reuse this
+/// instead of reimplementing it against their own copy of the loop.
+pub(crate) fn extract_tool_calls(result: &InferenceResult) -> Vec<ToolCall> {
+    let InferenceResult::Chat(chat) = result else {
+        return Vec::new();
+    };
+    chat.content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlockChatOutput::ToolCall(tool_call) => Some(tool_call.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `pub(crate)` for the same reason as [`extract_tool_calls`].
+pub(crate) fn tool_call_is_configured(tool_call: &ToolCall, tools_available: &[Tool]) -> bool {
+    tools_available
+        .iter()
+        .any(|tool| tool.name() == tool_call.name)
+}
+
+/// Tools named with a `may_` prefix are side-effecting by convention (borrowed from the same
+/// convention used by external agent tooling) and must be confirmed by a human before the
+/// gateway executes them, rather than being auto-executed like any other configured tool.
+fn is_confirmation_gated(tool_call: &ToolCall) -> bool {
+    tool_call.name.starts_with("may_")
+}
+
+/// `pub(crate)` for the same reason as [`extract_tool_calls`].
+pub(crate) fn assistant_turn_from_tool_calls(tool_calls: &[ToolCall]) -> ResolvedInputMessage {
+    ResolvedInputMessage {
+        role: Role::Assistant,
+        content: tool_calls
+            .iter()
+            .cloned()
+            .map(ResolvedInputMessageContent::ToolCall)
+            .collect(),
+    }
+}
+
+/// Executes a tool call against its configured executor, reusing a cached result instead of
+/// re-invoking the executor when `cache_ttl_seconds` is set and an earlier call with the same
+/// `(tool_name, raw_arguments)` is still within its TTL.
+///
+/// This only reuses the *tool* result, not the surrounding model call — unlike the cache already
+/// used for model inference (keyed by the full request plus `extra_cache_key`), so it cuts cost
+/// for repeated deterministic lookups (e.g. the same weather query recurring across a multi-step
+/// run) without touching the model-inference cache path at all. Flagging a cache hit in
+/// `model_inference_results`/`usage_considering_cached` so it's visible in observability would
+/// need fields on those types that this crate snapshot doesn't have, so that accounting isn't
+/// done here.
+///
+/// `pub(crate)` so `AgenticToolLoopConfig`'s loop dispatches through the same cache instead of
+/// keeping its own hand-rolled one; `clients.tool_result_cache` is shared across the whole
+/// request regardless of which loop is driving it.
+pub(crate) async fn execute_tool_call(
+    clients: &InferenceClients<'_>,
+    executor: &ToolExecutorConfig,
+    tool_call: &ToolCall,
+) -> Result<ToolResult, Error> {
+    let Some(ttl_seconds) = executor.cache_ttl_seconds else {
+        return dispatch_tool_call(clients, executor, tool_call).await;
+    };
+    if let Some(cached) = clients
+        .tool_result_cache
+        .get(&tool_call.name, &tool_call.arguments)
+    {
+        return Ok(ToolResult {
+            id: tool_call.id.clone(),
+            name: tool_call.name.clone(),
+            result: cached,
+        });
+    }
+    let result = dispatch_tool_call(clients, executor, tool_call).await?;
+    clients.tool_result_cache.insert(
+        &tool_call.name,
+        &tool_call.arguments,
+        result.result.clone(),
+        std::time::Duration::from_secs(ttl_seconds),
+    );
+    Ok(result)
+}
+
+/// POSTs a tool call's arguments to its configured executor and returns the response body as
+/// the tool result. The executor is expected to respond with the tool's result directly in the
+/// response body (success status required); anything else is surfaced as an inference error.
+///
+/// `pub(crate)` so other tool-executing variants (e.g. `AgenticToolLoopConfig`) can dispatch
+/// against the same `ToolExecutorConfig` rather than reimplementing the HTTP call.
+pub(crate) async fn dispatch_tool_call(
+    clients: &InferenceClients<'_>,
+    executor: &ToolExecutorConfig,
+    tool_call: &ToolCall,
+) -> Result<ToolResult, Error> {
+    let provider_type = "tool_executor";
+    let response = clients
+        .http_client
+        .post(&executor.url)
+        .header("content-type", "application/json")
+        .body(tool_call.arguments.clone())
+        .send()
+        .await
+        .map_err(|e| {
+            Error::new(ErrorDetails::InferenceClient {
+                message: format!("tool executor request to `{}` failed: {e}", executor.url),
+                raw_request: Some(tool_call.arguments.clone()),
+                raw_response: None,
+                status_code: None,
+                provider_type: provider_type.to_string(),
+            })
+        })?;
+    let status = response.status();
+    let body = response.text().await.map_err(|e| {
+        Error::new(ErrorDetails::InferenceClient {
+            message: format!(
+                "failed to read tool executor response from `{}`: {e}",
+                executor.url
+            ),
+            raw_request: Some(tool_call.arguments.clone()),
+            raw_response: None,
+            status_code: Some(status),
+            provider_type: provider_type.to_string(),
+        })
+    })?;
+    if !status.is_success() {
+        return Err(Error::new(ErrorDetails::InferenceClient {
+            message: format!("tool executor at `{}` returned {status}", executor.url),
+            raw_request: Some(tool_call.arguments.clone()),
+            raw_response: Some(body),
+            status_code: Some(status),
+            provider_type: provider_type.to_string(),
+        }));
+    }
+    Ok(ToolResult {
+        id: tool_call.id.clone(),
+        name: tool_call.name.clone(),
+        result: body,
+    })
+}
+
 fn prepare_request_message(
     message: &ResolvedInputMessage,
     templates: &TemplateConfig,
     template_name: Option<&str>,
     template_schema_info: TemplateSchemaInfo,
+    special_tokens: SpecialTokens<'_>,
 ) -> Result<RequestMessage, Error> {
     let mut content = Vec::new();
     // If a schema is provided, then we'll just use the `ResolvedInputMessageContent::Text`
@@ -326,7 +907,9 @@ fn prepare_request_message(
                                 Error::new(ErrorDetails::InvalidMessage { message: format!("Request message content {} is not a string but template (without schema) is provided for Role {}", value, message.role) })
                             })?;
                             Cow::Owned(serde_json::json!({
-                                template_var: message_text
+                                template_var: message_text,
+                                "bos_token": special_tokens.bos_token,
+                                "eos_token": special_tokens.eos_token,
                             }))
                         } else {
                             Cow::Borrowed(value)
@@ -387,29 +970,143 @@ impl Variant for ChatCompletionConfig {
         inference_params: InferenceParams,
     ) -> Result<InferenceResult, Error> {
         let mut inference_params = inference_params;
-        let request = self.prepare_request(
-            input,
-            function,
-            inference_config,
-            false,
-            &mut inference_params,
-        )?;
-        let model_config = models.models.get(&self.model).await?.ok_or_else(|| {
-            Error::new(ErrorDetails::UnknownModel {
-                name: self.model.to_string(),
-            })
-        })?;
-        let args = InferModelRequestArgs {
-            request,
-            model_name: self.model.clone(),
-            model_config: &model_config,
-            function,
-            inference_config,
-            clients,
-            inference_params,
-            retry_config: &self.retries,
-        };
-        infer_model_request(args).await
+        let Some(tool_executors) = &self.tool_executors else {
+            if let Some(best_of) = self.best_of.filter(|best_of| *best_of > 1) {
+                return self
+                    .infer_best_of(
+                        input,
+                        models,
+                        function,
+                        inference_config,
+                        clients,
+                        inference_params,
+                        best_of,
+                    )
+                    .await;
+            }
+            let request = self.prepare_request(
+                input,
+                function,
+                inference_config,
+                false,
+                &mut inference_params,
+            )?;
+            let model_config = models.models.get(&self.model).await?.ok_or_else(|| {
+                Error::new(ErrorDetails::UnknownModel {
+                    name: self.model.to_string(),
+                })
+            })?;
+            let args = InferModelRequestArgs {
+                request,
+                model_name: self.model.clone(),
+                model_config: &model_config,
+                function,
+                inference_config,
+                clients,
+                inference_params,
+                retry_config: &self.retries,
+            };
+            return infer_model_request(args).await;
+        };
+
+        // `tool_executors` is set: drive a bounded multi-step loop instead of a single model
+        // call, executing each step's tool calls over HTTP and feeding the results back in as
+        // the next step's input. Every step here is still a normal `infer_model_request` call,
+        // so it's recorded the same way a single-step inference would be; it's up to the
+        // caller (the endpoint that invokes `Variant::infer`) to persist each of those steps to
+        // ClickHouse the same way it already does for the one-step case, rather than this loop
+        // writing observability data itself. `Usage` is accumulated across every step below, so
+        // it stays accurate even though only the final step's `InferenceResult` is returned;
+        // doing the same for the raw per-step `ModelInferenceResponseWithMetadata` records would
+        // need `ChatInferenceResult`/`JsonInferenceResult` to grow a field to hold them, which
+        // belongs in `inference/types.rs` and isn't touched here.
+        let max_steps = self.max_tool_steps.max(1);
+        let tools_available: &[Tool] = inference_config
+            .tool_config
+            .map(|tool_config| tool_config.tools_available.as_slice())
+            .unwrap_or(&[]);
+        let mut running_input = input.clone();
+        let mut total_usage = Usage::default();
+        for step in 0..max_steps {
+            let request = self.prepare_request(
+                &running_input,
+                function,
+                inference_config,
+                false,
+                &mut inference_params,
+            )?;
+            let model_config = models.models.get(&self.model).await?.ok_or_else(|| {
+                Error::new(ErrorDetails::UnknownModel {
+                    name: self.model.to_string(),
+                })
+            })?;
+            let args = InferModelRequestArgs {
+                request,
+                model_name: self.model.clone(),
+                model_config: &model_config,
+                function,
+                inference_config,
+                clients,
+                inference_params: inference_params.clone(),
+                retry_config: &self.retries,
+            };
+            let mut result = infer_model_request(args).await?;
+            total_usage = sum_usage(total_usage, result_usage(&result));
+
+            let tool_calls = extract_tool_calls(&result);
+            if tool_calls.is_empty() {
+                set_result_usage(&mut result, total_usage);
+                return Ok(result);
+            }
+            // Unlike the empty-tool-calls case above, a step that still has tool calls left to
+            // execute can't just be handed back as the final answer: its content is a pending
+            // tool call, not a completed response. If this is also the last allotted step,
+            // there's no step left to execute those calls and feed the results back in, so this
+            // has to surface as exhaustion rather than silently returning a half-finished result.
+            let is_last_step = step + 1 == max_steps;
+            if is_last_step {
+                return Err(Error::new(ErrorDetails::ToolStepsExhausted {
+                    max_tool_steps: max_steps,
+                }));
+            }
+
+            let mut tool_results = Vec::with_capacity(tool_calls.len());
+            for tool_call in &tool_calls {
+                if is_confirmation_gated(tool_call) {
+                    // By convention, a tool named `may_*` is side-effecting and must not be
+                    // auto-executed: stop the loop here and return this step's result as the
+                    // final answer, so the caller can show the pending call to the user and
+                    // decide whether to confirm and execute it themselves.
+                    set_result_usage(&mut result, total_usage);
+                    return Ok(result);
+                }
+                let Some(executor) = tool_call_is_configured(tool_call, tools_available)
+                    .then(|| tool_executors.get(&tool_call.name))
+                    .flatten()
+                else {
+                    // The model called a tool that isn't configured (or has no registered
+                    // executor): stop the loop and return this step's result as the final
+                    // answer, the same as if the model had called no tools at all.
+                    set_result_usage(&mut result, total_usage);
+                    return Ok(result);
+                };
+                tool_results.push(execute_tool_call(clients, executor, tool_call).await?);
+            }
+
+            running_input
+                .messages
+                .push(assistant_turn_from_tool_calls(&tool_calls));
+            running_input.messages.push(ResolvedInputMessage {
+                role: Role::User,
+                content: tool_results
+                    .into_iter()
+                    .map(ResolvedInputMessageContent::ToolResult)
+                    .collect(),
+            });
+        }
+        Err(Error::new(ErrorDetails::ToolStepsExhausted {
+            max_tool_steps: max_steps,
+        }))
     }
 
     async fn infer_stream<'request>(
@@ -472,12 +1169,68 @@ impl Variant for ChatCompletionConfig {
         }
         models.validate(&self.model)?;
 
+        if self.tool_executors.is_some() && self.max_tool_steps == 0 {
+            return Err(Error::new(ErrorDetails::Config {
+                message: format!(
+                    "`functions.{function_name}.variants.{variant_name}`: `max_tool_steps` must be at least 1 when `tool_executors` is set"
+                ),
+            }));
+        }
+
+        if self.best_of == Some(0) {
+            return Err(Error::new(ErrorDetails::Config {
+                message: format!(
+                    "`functions.{function_name}.variants.{variant_name}`: `best_of` must be at least 1"
+                ),
+            }));
+        }
+
+        // `infer` only consults `best_of` on the single-call path (`self.tool_executors.is_none()`);
+        // the multi-step tool loop above always runs one candidate per step. Rather than let
+        // `best_of` silently do nothing when both are configured, reject the combination here.
+        if self.tool_executors.is_some() && self.best_of.is_some_and(|best_of| best_of > 1) {
+            return Err(Error::new(ErrorDetails::Config {
+                message: format!(
+                    "`functions.{function_name}.variants.{variant_name}`: `best_of` is not supported together with `tool_executors`"
+                ),
+            }));
+        }
+
+        if self.fim.is_some() && self.chat_template.is_some() {
+            return Err(Error::new(ErrorDetails::Config {
+                message: format!(
+                    "`functions.{function_name}.variants.{variant_name}`: `fim` and `chat_template` cannot both be set"
+                ),
+            }));
+        }
+
+        // `fim` assembles its prompt directly from the request's `prefix`/`suffix`, bypassing
+        // the per-role templates/schemas entirely, so there's nothing further to validate here.
+        if self.fim.is_some() {
+            return Ok(());
+        }
+
+        // `chat_template` renders the whole conversation itself and bypasses the per-role
+        // templates entirely, so the per-role schema checks below don't apply to it.
+        if let Some(chat_template) = &self.chat_template {
+            return self
+                .validate_chat_template(chat_template, templates)
+                .map_err(|e| {
+                    Error::new(ErrorDetails::Config {
+                        message: format!(
+                            "`functions.{function_name}.variants.{variant_name}.chat_template`: {e}"
+                        ),
+                    })
+                });
+        }
+
         // Validate the system template matches the system schema (best effort, we cannot check the variables comprehensively)
         validate_template_and_schema(
             TemplateKind::System,
             function.system_schema(),
             self.system_template.as_ref().map(|t| &t.path),
             templates,
+            self.special_tokens(),
         )
         .map_err(|e| {
             Error::new(ErrorDetails::Config {
@@ -493,6 +1246,7 @@ impl Variant for ChatCompletionConfig {
             function.user_schema(),
             self.user_template.as_ref().map(|t| &t.path),
             templates,
+            self.special_tokens(),
         )
         .map_err(|e| {
             Error::new(ErrorDetails::Config {
@@ -508,6 +1262,7 @@ impl Variant for ChatCompletionConfig {
             function.assistant_schema(),
             self.assistant_template.as_ref().map(|t| &t.path),
             templates,
+            self.special_tokens(),
         )
         .map_err(|e| {
             Error::new(ErrorDetails::Config {
@@ -530,6 +1285,9 @@ impl Variant for ChatCompletionConfig {
         if let Some(assistant_template) = &self.assistant_template {
             templates.push(assistant_template);
         }
+        if let Some(chat_template) = &self.chat_template {
+            templates.push(chat_template);
+        }
         templates
     }
 
@@ -595,6 +1353,35 @@ const SYSTEM_TEXT_TEMPLATE_VAR: &str = "system_text";
 const USER_TEXT_TEMPLATE_VAR: &str = "user_text";
 const ASSISTANT_TEXT_TEMPLATE_VAR: &str = "assistant_text";
 
+/// Model-specific special tokens (e.g. Llama/Mistral-style `bos_token`/`eos_token`) exposed to
+/// the no-schema template context alongside the `{system,user,assistant}_text` variable, so a
+/// chat template can be authored against the model's real prompt format instead of requiring
+/// application code to pre-format it.
+///
+/// The companion piece of this request — registering a `raise_exception` Jinja function so
+/// templates can reject malformed input the same way HuggingFace chat templates do — belongs
+/// in `minijinja_util::TemplateConfig::new`, where the `minijinja::Environment` is built; it's
+/// out of scope for this variant-level change. The registration itself is small:
+///
+/// ```rust,ignore
+/// env.add_function("raise_exception", |msg: String| -> Result<Value, minijinja::Error> {
+///     Err(minijinja::Error::new(ErrorKind::InvalidOperation, msg))
+/// });
+/// ```
+///
+/// `template_message`/`get_undeclared_variables` (called below, and from
+/// `validate_template_and_schema`) already convert any `minijinja::Error` a render produces —
+/// including one raised this way — into `ErrorDetails::MiniJinjaTemplateRender` with the
+/// template name attached, so once the function is registered, no caller here needs to change:
+/// a template that calls `raise_exception("age is required")` starts failing config validation
+/// (via the sample render in `validate_template_and_schema`) and inference with that exact
+/// message instead of a generic undefined-value error.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SpecialTokens<'a> {
+    pub bos_token: Option<&'a str>,
+    pub eos_token: Option<&'a str>,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum TemplateKind {
     System,
@@ -602,11 +1389,48 @@ pub enum TemplateKind {
     Assistant,
 }
 
+/// Names always available in a template's context regardless of schema: the two special tokens,
+/// wired up unconditionally by `prepare_request_message`/`prepare_system_message` even when the
+/// variant doesn't configure them.
+///
+/// `raise_exception`, the HuggingFace-style guard function documented on [`SpecialTokens`], is
+/// deliberately NOT included here: nothing registers it with the `minijinja::Environment` yet
+/// (that still belongs in `minijinja_util`), so a template that calls it fails the sample render
+/// below with an "unknown function" error today. Allowing it here would pass that same template
+/// at config-load time only for it to blow up on the first real request — the opposite of what
+/// this validation exists to catch. Add it back once the function is actually registered.
+const ALWAYS_AVAILABLE_TEMPLATE_VARS: [&str; 2] = ["bos_token", "eos_token"];
+
+/// Fails validation if `undeclared_vars` references a special-token variable the variant hasn't
+/// configured a value for (e.g. `{{ bos_token }}` with no `bos_token` set), so a template that
+/// depends on a model's special tokens is caught at config-load time instead of silently
+/// rendering against `null` on the first real request.
+fn require_special_tokens_configured(
+    undeclared_vars: &std::collections::HashSet<String>,
+    special_tokens: SpecialTokens<'_>,
+    kind: TemplateKind,
+) -> Result<(), Error> {
+    for (var, configured) in [
+        ("bos_token", special_tokens.bos_token.is_some()),
+        ("eos_token", special_tokens.eos_token.is_some()),
+    ] {
+        if undeclared_vars.contains(var) && !configured {
+            return Err(Error::new(ErrorDetails::Config {
+                message: format!(
+                    "{kind:?} template references `{var}`, but no `{var}` is configured for this variant"
+                ),
+            }));
+        }
+    }
+    Ok(())
+}
+
 pub fn validate_template_and_schema(
     kind: TemplateKind,
     schema: Option<&StaticJSONSchema>,
     template: Option<&TomlRelativePath>,
     templates: &TemplateConfig,
+    special_tokens: SpecialTokens<'_>,
 ) -> Result<(), Error> {
     match (schema, template) {
         (None, Some(template)) => {
@@ -620,43 +1444,176 @@ pub fn validate_template_and_schema(
                 TemplateKind::User => USER_TEXT_TEMPLATE_VAR,
                 TemplateKind::Assistant => ASSISTANT_TEXT_TEMPLATE_VAR,
             };
-            // When we have no schema, the template can have at most one variable
+            // When we have no schema, the template can only reference the one allowed variable
+            // (e.g. `system_text`) plus the special tokens, which are always available
+            // regardless of kind.
             if !undeclared_vars.is_empty() {
-                // If the template has any variables, it must be the one allowed variable (e.g. `system_text`)
-                // based on the template kind
-                let mut undeclared_vars = undeclared_vars.into_iter().collect::<Vec<_>>();
-                if undeclared_vars != [allowed_var.to_string()] {
+                let mut unexpected_vars = undeclared_vars
+                    .iter()
+                    .filter(|var| {
+                        var.as_str() != allowed_var
+                            && !ALWAYS_AVAILABLE_TEMPLATE_VARS.contains(&var.as_str())
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !unexpected_vars.is_empty() {
                     // Ensure that the error message is deterministic
-                    undeclared_vars.sort();
-                    let undeclared_vars_str = format!("[{}]", undeclared_vars.join(", "));
+                    unexpected_vars.sort();
+                    let unexpected_vars_str = format!("[{}]", unexpected_vars.join(", "));
                     return Err(Error::new(ErrorDetails::Config {
                         message:
-                            format!("template needs variables: {undeclared_vars_str} but only `{allowed_var}` is allowed when template has no schema")
+                            format!("template needs variables: {unexpected_vars_str} but only `{allowed_var}`, `bos_token`, and `eos_token` are allowed when template has no schema")
                                 .to_string(),
                     }));
                 }
+                require_special_tokens_configured(&undeclared_vars, special_tokens, kind)?;
             }
+            // Actually render the template against the one variable it's allowed to use, plus
+            // the special tokens (so a template referencing `bos_token`/`eos_token` renders the
+            // same way it would for a real request). This catches templates that reference
+            // undefined filters/functions or otherwise fail to render, turning what would be a
+            // runtime 500 on the first real request into a config error here.
+            let sample_context = serde_json::json!({
+                allowed_var: "example",
+                "bos_token": special_tokens.bos_token,
+                "eos_token": special_tokens.eos_token,
+            });
+            templates
+                .template_message(template_name, &sample_context)
+                .map_err(|e| {
+                    Error::new(ErrorDetails::Config {
+                        message: format!(
+                            "template failed to render with a sample `{allowed_var}` value: {e}"
+                        ),
+                    })
+                })?;
         }
         (Some(_), None) => {
             return Err(Error::new(ErrorDetails::Config {
                 message: "template is required when schema is specified".to_string(),
             }));
         }
-        _ => {}
+        (Some(schema), Some(template)) => {
+            let template_name = template
+                .path()
+                .to_str()
+                .ok_or_else(|| Error::new(ErrorDetails::InvalidTemplatePath))?;
+            let undeclared_vars = templates.get_undeclared_variables(template_name)?;
+            require_special_tokens_configured(&undeclared_vars, special_tokens, kind)?;
+            // Render against a minimal instance that satisfies the schema (required properties
+            // filled with type-appropriate dummy values), plus the special tokens, so templates
+            // that call custom functions, use unsupported filters, reference an unconfigured
+            // special token, or otherwise crash on well-formed input fail at config-load time
+            // rather than on the first real request.
+            let mut sample_context = synthesize_sample(schema.value());
+            if let Value::Object(sample_context) = &mut sample_context {
+                sample_context.insert("bos_token".to_string(), special_tokens.bos_token.into());
+                sample_context.insert("eos_token".to_string(), special_tokens.eos_token.into());
+            }
+            templates
+                .template_message(template_name, &sample_context)
+                .map_err(|e| {
+                    Error::new(ErrorDetails::Config {
+                        message: format!(
+                            "template failed to render with a sample value satisfying the schema: {e}"
+                        ),
+                    })
+                })?;
+        }
+        (None, None) => {}
     }
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashMap;
-    use std::path::PathBuf;
+/// Extracts the partial template names referenced via minijinja `{% include "..." %}` or
+/// `{% import "..." as ... %}` tags in a template's source. Only handles a single string-literal
+/// path inside the tag (covering both `'...'` and `"..."` quoting); dynamic includes like
+/// `{% include some_variable %}` aren't (and can't be) resolved statically.
+fn parse_referenced_partials(template_source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = template_source;
+    while let Some(tag_start) = rest.find("{%") {
+        let after_open = &rest[tag_start + 2..];
+        let Some(tag_end) = after_open.find("%}") else {
+            break;
+        };
+        let tag = after_open[..tag_end].trim();
+        let keyword_rest = tag
+            .strip_prefix("include")
+            .or_else(|| tag.strip_prefix("import"));
+        if let Some(keyword_rest) = keyword_rest {
+            if let Some(name) = parse_first_string_literal(keyword_rest) {
+                names.push(name);
+            }
+        }
+        rest = &after_open[tag_end + 2..];
+    }
+    names
+}
 
-    use super::*;
+/// Parses a single leading `'...'`/`"..."` string literal, returning its (unescaped) contents.
+fn parse_first_string_literal(s: &str) -> Option<String> {
+    let s = s.trim_start();
+    let quote = s.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let body = &s[quote.len_utf8()..];
+    let end = body.find(quote)?;
+    Some(body[..end].to_string())
+}
 
-    use futures::StreamExt;
-    use reqwest::Client;
-    use serde_json::{json, Value};
+/// Synthesizes a minimal JSON instance satisfying a (subset of) JSON Schema, used to render
+/// templates against representative sample data at config-load time. Only fills in properties
+/// that are actually `required`, since those are the only ones a template can rely on being
+/// present; an unrecognized or unsupported schema shape falls back to `null`.
+fn synthesize_sample(schema: &Value) -> Value {
+    let Some(schema) = schema.as_object() else {
+        return Value::Null;
+    };
+    if let Some(const_value) = schema.get("const") {
+        return const_value.clone();
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        return options.first().cloned().unwrap_or(Value::Null);
+    }
+    match schema.get("type").and_then(Value::as_str) {
+        Some("object") => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|required| required.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            let mut object = serde_json::Map::new();
+            if let Some(properties) = properties {
+                for field in &required {
+                    if let Some(field_schema) = properties.get(*field) {
+                        object.insert((*field).to_string(), synthesize_sample(field_schema));
+                    }
+                }
+            }
+            Value::Object(object)
+        }
+        Some("array") => {
+            let item_schema = schema.get("items").cloned().unwrap_or(Value::Bool(true));
+            Value::Array(vec![synthesize_sample(&item_schema)])
+        }
+        Some("string") => Value::String("example".to_string()),
+        Some("integer") => Value::Number(0.into()),
+        Some("number") => serde_json::json!(0.0),
+        Some("boolean") => Value::Bool(true),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    use futures::StreamExt;
+    use reqwest::Client;
+    use serde_json::{json, Value};
     use uuid::Uuid;
 
     use crate::cache::{CacheEnabledMode, CacheOptions};
@@ -675,6 +1632,7 @@ mod tests {
     use crate::providers::dummy::{DummyProvider, DUMMY_JSON_RESPONSE_RAW};
     use crate::providers::test_helpers::get_temperature_tool_config;
     use crate::tool::{ToolCallConfig, ToolChoice};
+    use crate::tool_cache::ToolResultCache;
     use crate::{
         error::Error,
         inference::types::{ContentBlockChunk, Role, TextChunk},
@@ -691,7 +1649,12 @@ mod tests {
             system_template: None,
             user_template: None,
             assistant_template: None,
+            chat_template: None,
+            fim: None,
+            tool_executors: None,
+            max_tool_steps: 1,
             json_mode: Some(JsonMode::On),
+            best_of: None,
             temperature: None,
             top_p: None,
             presence_penalty: None,
@@ -702,6 +1665,8 @@ mod tests {
             retries: RetryConfig::default(),
             extra_body: Default::default(),
             extra_headers: Default::default(),
+            bos_token: None,
+            eos_token: None,
         };
 
         let all_schemas = TemplateSchemaInfo {
@@ -1037,6 +2002,7 @@ mod tests {
         let client = Client::new();
         let clickhouse_connection_info = ClickHouseConnectionInfo::Disabled;
         let api_keys = InferenceCredentials::default();
+        let tool_result_cache = ToolResultCache::new();
         let clients = InferenceClients {
             http_client: &client,
             clickhouse_connection_info: &clickhouse_connection_info,
@@ -1045,6 +2011,7 @@ mod tests {
                 max_age_s: None,
                 enabled: CacheEnabledMode::WriteOnly,
             },
+            tool_result_cache: &tool_result_cache,
         };
         let templates = get_test_template_config();
         let system_template_name = "system";
@@ -1884,6 +2851,7 @@ mod tests {
         let client = Client::new();
         let clickhouse_connection_info = ClickHouseConnectionInfo::Disabled;
         let api_keys = InferenceCredentials::default();
+        let tool_result_cache = ToolResultCache::new();
         let clients = InferenceClients {
             http_client: &client,
             clickhouse_connection_info: &clickhouse_connection_info,
@@ -1892,6 +2860,7 @@ mod tests {
                 max_age_s: None,
                 enabled: CacheEnabledMode::WriteOnly,
             },
+            tool_result_cache: &tool_result_cache,
         };
         let templates = Box::leak(Box::new(get_test_template_config()));
         let schema_any = StaticJSONSchema::from_value(&json!({ "type": "object" })).unwrap();
@@ -2367,7 +3336,13 @@ mod tests {
     #[test]
     fn test_validate_template_and_schema_both_none() {
         let templates = get_test_template_config();
-        let result = validate_template_and_schema(TemplateKind::System, None, None, &templates);
+        let result = validate_template_and_schema(
+            TemplateKind::System,
+            None,
+            None,
+            &templates,
+            SpecialTokens::default(),
+        );
         assert!(result.is_ok());
     }
 
@@ -2379,12 +3354,14 @@ mod tests {
             PathBuf::new(),
         )
         .unwrap();
-        let template = PathBuf::from("test_validate_template_and_schema_both_some");
+        // Has no variables, so it renders successfully regardless of what the schema requires.
+        let template = PathBuf::from("system_filled");
         let result = validate_template_and_schema(
             TemplateKind::System,
             Some(&schema),
             Some(&TomlRelativePath::new_for_tests(template)),
             &templates,
+            SpecialTokens::default(),
         );
         assert!(result.is_ok());
     }
@@ -2398,6 +3375,7 @@ mod tests {
             None,
             Some(&TomlRelativePath::new_for_tests(template)),
             &templates,
+            SpecialTokens::default(),
         );
         assert!(result.is_ok());
     }
@@ -2411,6 +3389,7 @@ mod tests {
             None,
             Some(&TomlRelativePath::new_for_tests(template)),
             &templates,
+            SpecialTokens::default(),
         )
         .unwrap_err();
         let details = err.get_details();
@@ -2418,7 +3397,7 @@ mod tests {
         if let ErrorDetails::Config { message } = details {
             assert_eq!(
                 *message,
-                "template needs variables: [name] but only `system_text` is allowed when template has no schema".to_string()
+                "template needs variables: [name] but only `system_text`, `bos_token`, and `eos_token` are allowed when template has no schema".to_string()
             );
         } else {
             panic!("Expected Error::Config");
@@ -2433,9 +3412,14 @@ mod tests {
             PathBuf::new(),
         )
         .unwrap();
-        let err =
-            validate_template_and_schema(TemplateKind::System, Some(&schema), None, &templates)
-                .unwrap_err();
+        let err = validate_template_and_schema(
+            TemplateKind::System,
+            Some(&schema),
+            None,
+            &templates,
+            SpecialTokens::default(),
+        )
+        .unwrap_err();
         let details = err.get_details();
 
         if let ErrorDetails::Config { message } = details {
@@ -2447,4 +3431,366 @@ mod tests {
             panic!("Expected Error::Config");
         }
     }
+
+    #[test]
+    fn test_require_special_tokens_configured() {
+        let referencing_bos_token: std::collections::HashSet<String> =
+            ["bos_token".to_string()].into_iter().collect();
+
+        let err = require_special_tokens_configured(
+            &referencing_bos_token,
+            SpecialTokens::default(),
+            TemplateKind::User,
+        )
+        .unwrap_err();
+        if let ErrorDetails::Config { message } = err.get_details() {
+            assert_eq!(
+                *message,
+                "User template references `bos_token`, but no `bos_token` is configured for this variant"
+            );
+        } else {
+            panic!("Expected Error::Config");
+        }
+
+        let result = require_special_tokens_configured(
+            &referencing_bos_token,
+            SpecialTokens {
+                bos_token: Some("<s>"),
+                eos_token: None,
+            },
+            TemplateKind::User,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_synthesize_sample() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "age": {"type": "integer"},
+                "extra": {"type": "string"}
+            },
+            "required": ["name", "age"]
+        });
+        let sample = synthesize_sample(&schema);
+        // Only required properties are filled in, since those are all a template can rely on.
+        assert_eq!(sample, json!({"name": "example", "age": 0}));
+    }
+
+    #[test]
+    fn test_parse_referenced_partials() {
+        let template = r#"
+            {% import "macros/format.minijinja" as fmt %}
+            {% include 'partials/header.minijinja' %}
+            Hello, {{ name }}!
+        "#;
+        assert_eq!(
+            parse_referenced_partials(template),
+            vec![
+                "macros/format.minijinja".to_string(),
+                "partials/header.minijinja".to_string(),
+            ]
+        );
+        assert_eq!(
+            parse_referenced_partials("no tags here"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_infer_tool_executors_exhausted_on_last_step() {
+        let client = Client::new();
+        let clickhouse_connection_info = ClickHouseConnectionInfo::Disabled;
+        let api_keys = InferenceCredentials::default();
+        let tool_result_cache = ToolResultCache::new();
+        let clients = InferenceClients {
+            http_client: &client,
+            clickhouse_connection_info: &clickhouse_connection_info,
+            credentials: &api_keys,
+            cache_options: &CacheOptions {
+                max_age_s: None,
+                enabled: CacheEnabledMode::WriteOnly,
+            },
+            tool_result_cache: &tool_result_cache,
+        };
+        let templates = get_test_template_config();
+        let function_config = FunctionConfig::Chat(FunctionConfigChat {
+            variants: HashMap::new(),
+            system_schema: None,
+            user_schema: None,
+            assistant_schema: None,
+            tools: vec![],
+            tool_choice: ToolChoice::Auto,
+            parallel_tool_calls: None,
+            description: None,
+        });
+        let tool_provider_config = ProviderConfig::Dummy(DummyProvider {
+            model_name: "tool".into(),
+            ..Default::default()
+        });
+        let tool_model_config = ModelConfig {
+            routing: vec!["tool_provider".into()],
+            providers: HashMap::from([(
+                "tool_provider".into(),
+                ModelProvider {
+                    name: "tool_provider".into(),
+                    config: tool_provider_config,
+                    extra_body: Default::default(),
+                    extra_headers: Default::default(),
+                    timeouts: Default::default(),
+                    discard_unknown_chunks: false,
+                },
+            )]),
+            timeouts: Default::default(),
+        };
+        let models: ModelTable = HashMap::from([("tool".into(), tool_model_config)])
+            .try_into()
+            .unwrap();
+        let inference_models = InferenceModels {
+            models: &models,
+            embedding_models: &EmbeddingModelTable::default(),
+        };
+        let weather_tool_config = get_temperature_tool_config();
+        let inference_config = InferenceConfig {
+            templates: &templates,
+            tool_config: Some(&weather_tool_config),
+            function_name: "",
+            variant_name: "",
+            dynamic_output_schema: None,
+            ids: InferenceIds {
+                inference_id: Uuid::now_v7(),
+                episode_id: Uuid::now_v7(),
+            },
+            extra_body: Default::default(),
+            extra_headers: Default::default(),
+            extra_cache_key: None,
+        };
+        let input = ResolvedInput {
+            system: None,
+            messages: vec![ResolvedInputMessage {
+                role: Role::User,
+                content: vec!["What is the weather in Brooklyn?".to_string().into()],
+            }],
+        };
+        // `max_tool_steps: 1` means the single step this loop gets to run is also its last:
+        // the "tool" dummy model always responds with a tool call, so the loop must exhaust
+        // here rather than silently returning the pending tool call as a final answer. No
+        // entry is needed in `tool_executors` for this, since exhaustion is detected before
+        // any executor would be dispatched.
+        let chat_completion_config = ChatCompletionConfig {
+            model: "tool".into(),
+            weight: Some(1.0),
+            tool_executors: Some(HashMap::new()),
+            max_tool_steps: 1,
+            ..Default::default()
+        };
+        let err = chat_completion_config
+            .infer(
+                &input,
+                &inference_models,
+                &function_config,
+                &inference_config,
+                &clients,
+                InferenceParams::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err.get_details(),
+                ErrorDetails::ToolStepsExhausted { max_tool_steps: 1 }
+            ),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_rejects_best_of_with_tool_executors() {
+        let templates = get_test_template_config();
+        let function_config = FunctionConfig::Chat(FunctionConfigChat {
+            variants: HashMap::new(),
+            system_schema: None,
+            user_schema: None,
+            assistant_schema: None,
+            tools: vec![],
+            tool_choice: ToolChoice::Auto,
+            parallel_tool_calls: None,
+            description: None,
+        });
+        let good_provider_config = ProviderConfig::Dummy(DummyProvider {
+            model_name: "good".into(),
+            ..Default::default()
+        });
+        let text_model_config = ModelConfig {
+            routing: vec!["good".into()],
+            providers: HashMap::from([(
+                "good".into(),
+                ModelProvider {
+                    name: "good".into(),
+                    config: good_provider_config,
+                    extra_body: Default::default(),
+                    extra_headers: Default::default(),
+                    timeouts: Default::default(),
+                    discard_unknown_chunks: false,
+                },
+            )]),
+            timeouts: Default::default(),
+        };
+        let mut models: ModelTable = HashMap::from([("good".into(), text_model_config)])
+            .try_into()
+            .unwrap();
+        let embedding_models = EmbeddingModelTable::default();
+
+        let chat_completion_config = ChatCompletionConfig {
+            model: "good".into(),
+            weight: Some(1.0),
+            tool_executors: Some(HashMap::from([(
+                "get_temperature".to_string(),
+                ToolExecutorConfig {
+                    url: "http://example.invalid/tool".to_string(),
+                    cache_ttl_seconds: None,
+                },
+            )])),
+            max_tool_steps: 1,
+            best_of: Some(2),
+            ..Default::default()
+        };
+
+        let err = chat_completion_config
+            .validate(
+                &function_config,
+                &mut models,
+                &embedding_models,
+                &templates,
+                "my_function",
+                "my_variant",
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err.get_details(), ErrorDetails::Config { .. }),
+            "{}",
+            err
+        );
+        assert!(err
+            .to_string()
+            .contains("`best_of` is not supported together with `tool_executors`"));
+    }
+
+    #[tokio::test]
+    async fn test_infer_best_of_sums_usage_across_candidates() {
+        // `DummyProvider`'s "good" response doesn't vary by seed, so every candidate here is
+        // identical; this can't exercise `select_best_candidate_index` actually picking a
+        // non-first candidate, but it does verify the two things `infer_best_of`'s doc comment
+        // promises regardless of which candidate wins: output tokens summed across every
+        // candidate sampled, and input tokens counted once rather than once per candidate.
+        let client = Client::new();
+        let clickhouse_connection_info = ClickHouseConnectionInfo::Disabled;
+        let api_keys = InferenceCredentials::default();
+        let tool_result_cache = ToolResultCache::new();
+        let clients = InferenceClients {
+            http_client: &client,
+            clickhouse_connection_info: &clickhouse_connection_info,
+            credentials: &api_keys,
+            cache_options: &CacheOptions {
+                max_age_s: None,
+                enabled: CacheEnabledMode::WriteOnly,
+            },
+            tool_result_cache: &tool_result_cache,
+        };
+        let templates = get_test_template_config();
+        let function_config = FunctionConfig::Chat(FunctionConfigChat {
+            variants: HashMap::new(),
+            system_schema: None,
+            user_schema: None,
+            assistant_schema: None,
+            tools: vec![],
+            tool_choice: ToolChoice::Auto,
+            parallel_tool_calls: None,
+            description: None,
+        });
+        let good_provider_config = ProviderConfig::Dummy(DummyProvider {
+            model_name: "good".into(),
+            ..Default::default()
+        });
+        let text_model_config = ModelConfig {
+            routing: vec!["good".into()],
+            providers: HashMap::from([(
+                "good".into(),
+                ModelProvider {
+                    name: "good".into(),
+                    config: good_provider_config,
+                    extra_body: Default::default(),
+                    extra_headers: Default::default(),
+                    timeouts: Default::default(),
+                    discard_unknown_chunks: false,
+                },
+            )]),
+            timeouts: Default::default(),
+        };
+        let models: ModelTable = HashMap::from([("good".into(), text_model_config)])
+            .try_into()
+            .unwrap();
+        let inference_models = InferenceModels {
+            models: &models,
+            embedding_models: &EmbeddingModelTable::default(),
+        };
+        let inference_config = InferenceConfig {
+            templates: &templates,
+            tool_config: None,
+            function_name: "",
+            variant_name: "",
+            dynamic_output_schema: None,
+            ids: InferenceIds {
+                inference_id: Uuid::now_v7(),
+                episode_id: Uuid::now_v7(),
+            },
+            extra_body: Default::default(),
+            extra_headers: Default::default(),
+            extra_cache_key: None,
+        };
+        let input = ResolvedInput {
+            system: None,
+            messages: vec![ResolvedInputMessage {
+                role: Role::User,
+                content: vec!["Hello".to_string().into()],
+            }],
+        };
+        let chat_completion_config = ChatCompletionConfig {
+            model: "good".into(),
+            weight: Some(1.0),
+            best_of: Some(3),
+            ..Default::default()
+        };
+        let result = chat_completion_config
+            .infer(
+                &input,
+                &inference_models,
+                &function_config,
+                &inference_config,
+                &clients,
+                InferenceParams::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.usage_considering_cached(),
+            Usage {
+                input_tokens: 10,
+                output_tokens: 3,
+            }
+        );
+        match result {
+            InferenceResult::Chat(chat_response) => {
+                assert_eq!(
+                    chat_response.content,
+                    vec![DUMMY_INFER_RESPONSE_CONTENT.to_string().into()]
+                );
+            }
+            _ => panic!("Expected Chat inference response"),
+        }
+    }
 }
@@ -0,0 +1,233 @@
+//! Legacy prompt-style completion, for base/instruct models and `/completions`-style workloads
+//! migrating onto TensorZero without a chat template.
+//!
+//! The full version of this request — a `CompletionConfig` variant that calls the provider's
+//! completions endpoint and returns a new `InferenceResult::Completion` carrying raw text,
+//! usage, and a stop reason — needs two things this crate snapshot doesn't have: `InferenceResult`
+//! (in `inference/types.rs`) would need a `Completion` variant, and `FunctionConfig` (in
+//! `function.rs`) would need a third arm alongside `Chat`/`Json` so a function's config even
+//! accepts a completion-mode variant. Neither file is present here, so a real `Variant` impl
+//! returning `InferenceResult::Completion` can't be wired up end to end in this tree.
+//!
+//! What's reachable without them: assembling the prompt itself, and a `ModelInferenceRequest` for
+//! it with the same sampling-param validation/backfill `ChatCompletionConfig::prepare_request`
+//! uses (see `test_prepare_request_params`), just skipping the role/schema assembly a chat
+//! request needs. [`CompletionPromptConfig`] wraps a [`ChatCompletionConfig`] to reuse its
+//! `system_template`/`user_template` rendering (and, by extension, its model,
+//! `ChatCompletionInferenceParams`, and retries) so a config migrating from chat to completion
+//! mode keeps everything except the message-array shape, [`render_completion_prompt`]
+//! concatenates the rendered system/user text into the single string a `/completions` endpoint
+//! expects, and [`CompletionPromptConfig::prepare_request`] wraps that single string as the one
+//! `RequestMessage` a `ModelInferenceRequest` needs, with no separate system message and no
+//! output schema/JSON mode.
+
+use crate::endpoints::inference::InferenceParams;
+use crate::error::{Error, ErrorDetails};
+use crate::function::FunctionConfig;
+use crate::inference::types::extra_body::FullExtraBodyConfig;
+use crate::inference::types::extra_headers::FullExtraHeadersConfig;
+use crate::inference::types::ResolvedInput;
+use crate::inference::types::{ContentBlock, ModelInferenceRequest, RequestMessage, Role};
+use crate::minijinja_util::TemplateConfig;
+use crate::variant::chat_completion::{ChatCompletionConfig, TemplateSchemaInfo};
+use crate::variant::{prepare_model_inference_request, InferenceConfig};
+use serde::{Deserialize, Serialize};
+
+/// Wraps a [`ChatCompletionConfig`] to reuse its templating, model, sampling params, and retries
+/// under a completions-style prompt instead of a chat message array.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(ts_rs::TS))]
+#[cfg_attr(test, ts(export))]
+pub struct CompletionPromptConfig {
+    pub chat_completion: ChatCompletionConfig,
+}
+
+impl CompletionPromptConfig {
+    /// Renders `system_template` and `user_template` against `input` the same way
+    /// `ChatCompletionConfig::infer` would for a single-turn chat request, then flattens the
+    /// result into the single prompt string a completions call expects. `input` is expected to
+    /// carry its one turn as a single user message, since a completions call has no separate
+    /// assistant turns to render.
+    pub fn render_prompt(
+        &self,
+        templates: &TemplateConfig,
+        input: &ResolvedInput,
+        template_schema_info: TemplateSchemaInfo,
+    ) -> Result<String, Error> {
+        let system = self.chat_completion.prepare_system_message(
+            templates,
+            input.system.as_ref(),
+            template_schema_info,
+        )?;
+        let user_message = input
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.role == Role::User)
+            .ok_or_else(|| {
+                Error::new(ErrorDetails::Config {
+                    message: "completion mode requires at least one user message".to_string(),
+                })
+            })?;
+        let rendered = self.chat_completion.prepare_request_message(
+            templates,
+            user_message,
+            template_schema_info,
+        )?;
+        let user_text = rendered
+            .content
+            .iter()
+            .map(content_block_text)
+            .collect::<Result<Vec<_>, _>>()?
+            .join("");
+        Ok(render_completion_prompt(system.as_deref(), &user_text))
+    }
+
+    /// Builds the `ModelInferenceRequest` for this prompt: a single user `RequestMessage`
+    /// carrying the flattened text [`Self::render_prompt`] produces, with no separate system
+    /// message and no output schema/JSON mode, since completion mode has no role structure or
+    /// schema to apply either of those to. Sampling params (`temperature`, `max_tokens`, `seed`,
+    /// `top_p`, `presence_penalty`, `frequency_penalty`, `stop_sequences`) are validated and
+    /// backfilled into `inference_params` the same way `ChatCompletionConfig::prepare_request`
+    /// does, per `test_prepare_request_params`.
+    pub fn prepare_request<'a, 'request>(
+        &'a self,
+        input: &ResolvedInput,
+        function: &'a FunctionConfig,
+        inference_config: &'request InferenceConfig<'a, 'request>,
+        stream: bool,
+        inference_params: &mut InferenceParams,
+    ) -> Result<ModelInferenceRequest<'request>, Error> {
+        let prompt = self.render_prompt(
+            inference_config.templates,
+            input,
+            function.template_schema_info(),
+        )?;
+        let messages = vec![RequestMessage {
+            role: Role::User,
+            content: vec![prompt.into()],
+        }];
+
+        inference_params
+            .chat_completion
+            .backfill_with_variant_params(
+                self.chat_completion.temperature,
+                self.chat_completion.max_tokens,
+                self.chat_completion.seed,
+                self.chat_completion.top_p,
+                self.chat_completion.presence_penalty,
+                self.chat_completion.frequency_penalty,
+                self.chat_completion.stop_sequences.clone(),
+            );
+
+        let extra_body = FullExtraBodyConfig {
+            extra_body: self.chat_completion.extra_body.clone(),
+            inference_extra_body: inference_config
+                .extra_body
+                .clone()
+                .into_owned()
+                .filter(inference_config.variant_name),
+        };
+        let extra_headers = FullExtraHeadersConfig {
+            variant_extra_headers: self.chat_completion.extra_headers.clone(),
+            inference_extra_headers: inference_config
+                .extra_headers
+                .clone()
+                .into_owned()
+                .filter(inference_config.variant_name),
+        };
+
+        prepare_model_inference_request(
+            messages,
+            None,
+            function,
+            inference_config,
+            stream,
+            inference_params,
+            None,
+            extra_body,
+            extra_headers,
+        )
+    }
+}
+
+/// Concatenates a rendered system message and a rendered user message into a single prompt
+/// string suitable for a `/completions`-style endpoint, in the same order a chat request would
+/// present them as separate messages.
+fn render_completion_prompt(system: Option<&str>, user: &str) -> String {
+    match system {
+        Some(system) if !system.is_empty() => format!("{system}\n\n{user}"),
+        _ => user.to_string(),
+    }
+}
+
+/// Unlike a chat request, a completions prompt has no slot for non-text content: there's nowhere
+/// to put a tool result or a file attachment in a single flattened string, so (matching
+/// `plain_text_content`'s treatment of non-text chat content) this errors instead of silently
+/// dropping the block.
+fn content_block_text(block: &ContentBlock) -> Result<&str, Error> {
+    match block {
+        ContentBlock::Text(text) => Ok(text.text.as_str()),
+        other => Err(Error::new(ErrorDetails::InvalidMessage {
+            message: format!(
+                "completion mode only supports plain text message content; got {other:?}"
+            ),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::types::{ResolvedInputMessage, ResolvedInputMessageContent};
+    use crate::minijinja_util::tests::get_test_template_config;
+    use crate::tool::ToolResult;
+
+    fn no_schemas() -> TemplateSchemaInfo {
+        TemplateSchemaInfo {
+            has_system_schema: false,
+            has_user_schema: false,
+            has_assistant_schema: false,
+        }
+    }
+
+    #[test]
+    fn test_render_prompt_plain_text() {
+        let templates = get_test_template_config();
+        let config = CompletionPromptConfig::default();
+        let input = ResolvedInput {
+            system: None,
+            messages: vec![ResolvedInputMessage {
+                role: Role::User,
+                content: vec!["What is the weather in Brooklyn?".to_string().into()],
+            }],
+        };
+        let prompt = config
+            .render_prompt(&templates, &input, no_schemas())
+            .unwrap();
+        assert_eq!(prompt, "What is the weather in Brooklyn?");
+    }
+
+    #[test]
+    fn test_render_prompt_errors_on_non_text_content() {
+        let templates = get_test_template_config();
+        let config = CompletionPromptConfig::default();
+        let input = ResolvedInput {
+            system: None,
+            messages: vec![ResolvedInputMessage {
+                role: Role::User,
+                content: vec![ResolvedInputMessageContent::ToolResult(ToolResult {
+                    id: "0".to_string(),
+                    name: "get_temperature".to_string(),
+                    result: "70".to_string(),
+                })],
+            }],
+        };
+        let err = config
+            .render_prompt(&templates, &input, no_schemas())
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("completion mode only supports plain text message content"));
+    }
+}